@@ -0,0 +1,183 @@
+use crate::enums::*;
+use crate::pathfinding::DistanceMap;
+use crate::rules::rules;
+use crate::structs::*;
+
+impl GameActionSkeleton {
+    // Everything a city can do apart from buildings, in menu order.
+    pub const BASIC: [GameActionSkeleton; 9] = [
+        GameActionSkeleton::Produce,
+        GameActionSkeleton::UpgradeAttack,
+        GameActionSkeleton::UpgradeProduce,
+        GameActionSkeleton::GenerateCity,
+        GameActionSkeleton::AttackCity,
+        GameActionSkeleton::CaptureCity,
+        GameActionSkeleton::DestroyWall,
+        GameActionSkeleton::TransferResources,
+        GameActionSkeleton::SendConvoy,
+    ];
+
+    pub fn target_kind(self) -> TargetKind {
+        match self {
+            GameActionSkeleton::AttackCity => TargetKind::EnemyCity,
+            GameActionSkeleton::CaptureCity => TargetKind::CapturableCity,
+            GameActionSkeleton::DestroyWall => TargetKind::Obstacle,
+            GameActionSkeleton::GenerateCity => TargetKind::EmptyTile,
+            GameActionSkeleton::TransferResources | GameActionSkeleton::SendConvoy => {
+                TargetKind::OwnCity
+            }
+            GameActionSkeleton::Produce
+            | GameActionSkeleton::UpgradeAttack
+            | GameActionSkeleton::UpgradeProduce
+            | GameActionSkeleton::Build(_) => TargetKind::None,
+        }
+    }
+
+    pub fn needs_target(self) -> bool {
+        self.target_kind() != TargetKind::None
+    }
+}
+
+impl TargetKind {
+    pub fn description(self) -> &'static str {
+        match self {
+            TargetKind::None => "No target needed.",
+            TargetKind::EnemyCity => "Target: an enemy, neutral or barbarian city in range.",
+            TargetKind::CapturableCity => "Target: an enemy or neutral city in range.",
+            TargetKind::Obstacle => "Target: a wall or ruins in build range.",
+            TargetKind::EmptyTile => "Target: an empty tile in build range.",
+            TargetKind::OwnCity => "Target: another of your cities.",
+        }
+    }
+}
+
+impl Game {
+    // What `city` has to pay up front for an action. Transfers also pay per tile.
+    pub fn action_cost(&self, action: GameActionSkeleton, city: &City) -> u32 {
+        match action {
+            GameActionSkeleton::Produce
+            | GameActionSkeleton::AttackCity
+            | GameActionSkeleton::CaptureCity
+            | GameActionSkeleton::TransferResources
+            | GameActionSkeleton::SendConvoy => 0,
+            GameActionSkeleton::UpgradeAttack => city.get_resource_amount_to_upgrade_attack(),
+            GameActionSkeleton::UpgradeProduce => city.get_resource_amount_to_upgrade_produce(),
+            GameActionSkeleton::DestroyWall => self
+                .demolish_cost(city.state, false)
+                .min(self.demolish_cost(city.state, true)),
+            GameActionSkeleton::GenerateCity => rules().city_found_cost,
+            GameActionSkeleton::Build(building) => building.cost(),
+        }
+    }
+
+    // Every action the city at `source` has in this game, each with what it
+    // costs, what it targets, and what (if anything) stands in the way.
+    pub fn action_options(&self, source: TerminalPos) -> Result<Vec<ActionOption>, GameError> {
+        let city = *self.get_source_city_at_pos(source)?;
+        let distances = self.distance_map(source);
+
+        let buildings = Building::ALL
+            .into_iter()
+            .filter(|&building| self.can_build(building))
+            .map(GameActionSkeleton::Build);
+        let options = GameActionSkeleton::BASIC
+            .into_iter()
+            .chain(buildings)
+            .map(|action| {
+                let cost = self.action_cost(action, &city);
+                ActionOption {
+                    action,
+                    cost,
+                    target: action.target_kind(),
+                    blocked: self.action_blocked(action, &city, source, cost, &distances),
+                }
+            })
+            .collect();
+        Ok(options)
+    }
+
+    fn action_blocked(
+        &self,
+        action: GameActionSkeleton,
+        city: &City,
+        source: TerminalPos,
+        cost: u32,
+        distances: &DistanceMap,
+    ) -> Option<GameError> {
+        if city.has_acted {
+            return Some(GameError::CityAlreadyActed);
+        }
+        match action {
+            GameActionSkeleton::GenerateCity if rules().city_founding == 0 => {
+                return Some(GameError::ActionDisabled);
+            }
+            GameActionSkeleton::Build(building) if city.has_building(building) => {
+                return Some(GameError::AlreadyBuilt);
+            }
+            GameActionSkeleton::TransferResources | GameActionSkeleton::SendConvoy
+                if city.resources == 0 =>
+            {
+                return Some(GameError::NotEnoughResources);
+            }
+            _ => {}
+        }
+        if city.resources < cost {
+            return Some(GameError::NotEnoughResources);
+        }
+        if action.needs_target() && !self.has_target(action, city, source, distances) {
+            return Some(GameError::NoTargetInRange);
+        }
+        None
+    }
+
+    // Mirrors the checks `do_action` makes on the target.
+    fn has_target(
+        &self,
+        action: GameActionSkeleton,
+        city: &City,
+        source: TerminalPos,
+        distances: &DistanceMap,
+    ) -> bool {
+        let fee = self.transfer_fee_per_step(city.state);
+        let build_range = self.build_range(city.state);
+
+        self.grid.iter().enumerate().any(|(y, row)| {
+            row.iter().enumerate().any(|(x, cell)| {
+                let Some(distance) = distances[y][x] else {
+                    return false;
+                };
+                if TerminalPos(x as u16, y as u16) == source {
+                    return false;
+                }
+                let hostile = cell
+                    .city
+                    .filter(|c| c.state != CityState::Destroyed && c.state != city.state);
+                let ruins = cell.city.is_some_and(|c| c.state == CityState::Destroyed);
+                let own = cell.city.is_some_and(|c| c.state == city.state);
+
+                match action.target_kind() {
+                    TargetKind::None => true,
+                    TargetKind::EnemyCity => hostile.is_some() && distance <= rules().attack_range,
+                    TargetKind::CapturableCity => {
+                        hostile.is_some_and(|c| c.state != CityState::Barbarian)
+                            && distance <= rules().attack_range
+                    }
+                    TargetKind::Obstacle => {
+                        (cell.is_wall() || ruins)
+                            && distance <= build_range
+                            && city.resources >= self.demolish_cost(city.state, ruins)
+                    }
+                    TargetKind::EmptyTile => {
+                        cell.terrain.is_buildable()
+                            && cell.city.is_none()
+                            && distance <= build_range
+                    }
+                    TargetKind::OwnCity => {
+                        own && (action != GameActionSkeleton::TransferResources
+                            || distance * fee < city.resources)
+                    }
+                }
+            })
+        })
+    }
+}
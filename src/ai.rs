@@ -0,0 +1,236 @@
+use crate::enums::*;
+use crate::rules::rules;
+use crate::structs::*;
+
+impl Game {
+    fn nearest_in_range(
+        &self,
+        source: TerminalPos,
+        range: u32,
+        wanted: impl Fn(&Cell) -> bool,
+    ) -> Option<(TerminalPos, u32)> {
+        let distances = self.distance_map(source);
+        let mut best: Option<(TerminalPos, u32)> = None;
+
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let Some(distance) = distances[y][x] else {
+                    continue;
+                };
+                if distance == 0 || distance > range || !wanted(cell) {
+                    continue;
+                }
+                if best.is_none_or(|(_, best_distance)| distance < best_distance) {
+                    best = Some((TerminalPos(x as u16, y as u16), distance));
+                }
+            }
+        }
+
+        best
+    }
+
+    // The buildable cell in range that gets closest to the player's cities.
+    fn expansion_target(&self, source: TerminalPos) -> Option<TerminalPos> {
+        let distances = self.distance_map(source);
+        let build_range = self.build_range(CityState::OwnedByComputer);
+        let enemies = self.city_positions(Some(CityState::OwnedByPlayer));
+        let closeness = |pos: TerminalPos| {
+            enemies
+                .iter()
+                .map(|enemy| enemy.0.abs_diff(pos.0) + enemy.1.abs_diff(pos.1))
+                .min()
+                .unwrap_or(0)
+        };
+
+        let mut best: Option<TerminalPos> = None;
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let pos = TerminalPos(x as u16, y as u16);
+                if !cell.terrain.is_buildable() || cell.city.is_some() {
+                    continue;
+                }
+                if distances[y][x].is_none_or(|distance| distance > build_range) {
+                    continue;
+                }
+                if best.is_none_or(|best| closeness(pos) < closeness(best)) {
+                    best = Some(pos);
+                }
+            }
+        }
+
+        best
+    }
+
+    // The closest of the computer's other cities that has a player city in range.
+    fn nearest_frontline(&self, source: TerminalPos) -> Option<(TerminalPos, u32)> {
+        let is_enemy = |cell: &Cell| {
+            cell.city
+                .is_some_and(|c| c.state == CityState::OwnedByPlayer)
+        };
+        let distances = self.distance_map(source);
+
+        self.city_positions(Some(CityState::OwnedByComputer))
+            .into_iter()
+            .filter(|&pos| pos != source)
+            .filter(|&pos| {
+                self.nearest_in_range(pos, rules().attack_range, is_enemy)
+                    .is_some()
+            })
+            .filter_map(|pos| Some((pos, distances[pos.1 as usize][pos.0 as usize]?)))
+            .min_by_key(|&(_, distance)| distance)
+    }
+
+    // Picks a single action for one of the computer's cities. The computer
+    // sees everything, so it only attacks when the odds look fair.
+    pub fn choose_computer_action(&self, source: TerminalPos) -> Option<GameAction> {
+        let city = *self.get_source_city_at_pos(source).ok()?;
+        let options = self.action_options(source).ok()?;
+        let allowed = |action: GameActionSkeleton| {
+            options
+                .iter()
+                .any(|option| option.action == action && option.blocked.is_none())
+        };
+        // Producing is always possible, unless the city has already acted.
+        if !allowed(GameActionSkeleton::Produce) {
+            return None;
+        }
+
+        let is_enemy = |cell: &Cell| {
+            cell.city
+                .is_some_and(|c| c.state == CityState::OwnedByPlayer)
+        };
+        let is_hostile = |cell: &Cell| {
+            cell.city
+                .is_some_and(|c| c.state != city.state && c.state != CityState::Destroyed)
+        };
+
+        let target = self
+            .nearest_in_range(source, rules().attack_range, is_enemy)
+            .or_else(|| self.nearest_in_range(source, rules().attack_range, is_hostile));
+        if let Some((target, _)) = target {
+            let defender = *self.get_city_at_pos(target).ok()?;
+            // Whether an attack is likely enough to succeed, against a percentage.
+            let worth_it = |intent, percent: u32| {
+                self.combat_preview(source, target, intent)
+                    .is_ok_and(|preview| preview.success * 100.0 >= f64::from(percent))
+            };
+            // Only go for a capture when the odds comfortably allow for it.
+            if defender.state != CityState::Barbarian
+                && worth_it(AttackIntent::Capture, rules().ai_capture_odds_percent)
+            {
+                return Some(GameAction::AttackCity {
+                    source,
+                    target,
+                    intent: AttackIntent::Capture,
+                });
+            }
+            if worth_it(AttackIntent::Raze, rules().ai_raze_odds_percent) {
+                return Some(GameAction::AttackCity {
+                    source,
+                    target,
+                    intent: AttackIntent::Raze,
+                });
+            }
+            if allowed(GameActionSkeleton::UpgradeAttack) {
+                return Some(GameAction::UpgradeAttack { source });
+            }
+            if allowed(GameActionSkeleton::Build(Building::Walls)) {
+                return Some(GameAction::Build {
+                    source,
+                    building: Building::Walls,
+                });
+            }
+            // Save up for the next combat upgrade instead.
+            return Some(GameAction::Produce { source });
+        }
+
+        // Walled in: dig towards the enemy.
+        let can_reach_enemy = self.nearest_in_range(source, u32::MAX, is_enemy).is_some();
+        if !can_reach_enemy && allowed(GameActionSkeleton::DestroyWall) {
+            // Walls and ruins cost different amounts, so only aim for one it can pay for.
+            let is_obstacle = |cell: &Cell| {
+                let ruins = cell.city.is_some_and(|c| c.state == CityState::Destroyed);
+                (cell.is_wall() || ruins) && city.resources >= self.demolish_cost(city.state, ruins)
+            };
+            let build_range = self.build_range(city.state);
+            if let Some((target, _)) = self.nearest_in_range(source, build_range, is_obstacle) {
+                return Some(GameAction::DestroyWall { source, target });
+            }
+        }
+
+        if city.generation_level <= city.combat_level && allowed(GameActionSkeleton::UpgradeProduce)
+        {
+            return Some(GameAction::UpgradeProduce { source });
+        }
+
+        if allowed(GameActionSkeleton::GenerateCity)
+            && let Some(target) = self.expansion_target(source)
+        {
+            return Some(GameAction::GenerateCity { source, target });
+        }
+
+        if allowed(GameActionSkeleton::Build(Building::Wonder)) {
+            return Some(GameAction::Build {
+                source,
+                building: Building::Wonder,
+            });
+        }
+
+        if allowed(GameActionSkeleton::Build(Building::Market)) {
+            return Some(GameAction::Build {
+                source,
+                building: Building::Market,
+            });
+        }
+
+        // Backline cities feed the front instead of arming themselves.
+        if city.resources >= rules().supply_threshold
+            && let Some((target, distance)) = self.nearest_frontline(source)
+        {
+            let fee = distance * self.transfer_fee_per_step(city.state);
+            return Some(if fee * 4 <= city.resources {
+                GameAction::TransferResources { source, target }
+            } else {
+                GameAction::SendConvoy { source, target }
+            });
+        }
+
+        if allowed(GameActionSkeleton::UpgradeAttack) {
+            return Some(GameAction::UpgradeAttack { source });
+        }
+
+        Some(GameAction::Produce { source })
+    }
+
+    // Research the cheapest technology the computer can comfortably afford.
+    pub fn choose_computer_research(&self) -> Option<Tech> {
+        let owner = CityState::OwnedByComputer;
+        let spare = self.total_resources(owner);
+        Tech::ALL
+            .into_iter()
+            .filter(|&tech| self.can_research(owner, tech).is_ok())
+            .filter(|tech| spare >= tech.cost() + rules().supply_threshold)
+            .min_by_key(|tech| tech.cost())
+    }
+
+    // Every barbarian camp lashes out at the closest city it can reach.
+    pub fn barbarian_raids(&mut self) {
+        self.begin_turn(CityState::Barbarian);
+        for source in self.city_positions(Some(CityState::Barbarian)) {
+            let is_victim = |cell: &Cell| {
+                cell.city.is_some_and(|c| {
+                    c.state != CityState::Barbarian && c.state != CityState::Destroyed
+                })
+            };
+            if let Some((target, _)) =
+                self.nearest_in_range(source, rules().attack_range, is_victim)
+            {
+                let _ = self.do_action(GameAction::AttackCity {
+                    source,
+                    target,
+                    intent: AttackIntent::Raze,
+                });
+            }
+        }
+    }
+}
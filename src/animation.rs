@@ -0,0 +1,199 @@
+use crossterm::event::{self, Event, KeyEventKind, MouseEventKind};
+use crossterm::style::Color;
+use rand::Rng;
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use crate::enums::*;
+use crate::render::present;
+use crate::settings::settings;
+use crate::shared::*;
+use crate::structs::*;
+use crate::theme::theme;
+
+const FRAME_TIME: Duration = Duration::from_millis(80);
+// Frames of dice tumbling before they land, then of the target flashing.
+const ROLLING_FRAMES: usize = 8;
+const FLASH_FRAMES: usize = 6;
+const EXPLOSION: [char; 3] = ['✦', '✸', '✺'];
+const ASCII_EXPLOSION: [char; 3] = ['*', 'X', '#'];
+const BANNER_FRAMES: usize = 9;
+
+// Waits out one frame. Returns true if a key or click asked to skip ahead.
+fn wait() -> io::Result<bool> {
+    let end = Instant::now() + FRAME_TIME;
+    loop {
+        let remaining = end.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || !event::poll(remaining)? {
+            return Ok(false);
+        }
+        // Mouse movement and resizes don't skip, and the next frame picks up a resize.
+        let skip = match event::read()? {
+            Event::Key(key) => key.kind == KeyEventKind::Press,
+            Event::Mouse(mouse) => matches!(mouse.kind, MouseEventKind::Down(_)),
+            _ => false,
+        };
+        if skip {
+            return Ok(true);
+        }
+    }
+}
+
+// Draws over one grid cell, the same three columns `draw_grid` uses.
+fn put_cell(
+    frame: &mut Frame,
+    layout: Layout,
+    pos: TerminalPos,
+    text: &str,
+    background: Color,
+    foreground: Color,
+) {
+    let screen_x = (GRID_X_OFFSET + pos.0 as usize * 3) as u16;
+    frame.put(
+        screen_x,
+        layout.grid_y(pos.1 as usize),
+        text,
+        background,
+        foreground,
+    );
+}
+
+impl Game {
+    // Plays everything the engine queued up since last time. A key press skips
+    // the rest, and with animations turned off they're just thrown away.
+    pub fn play_animations(&mut self) -> io::Result<()> {
+        let animations = std::mem::take(&mut self.animations);
+        if !settings().animations {
+            return Ok(());
+        }
+        for animation in animations {
+            if self.play(animation)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // Returns true if it was skipped.
+    fn play(&self, animation: Animation) -> io::Result<bool> {
+        let theme = theme();
+        let frames = match animation {
+            Animation::Attack { .. } => ROLLING_FRAMES + FLASH_FRAMES,
+            Animation::Ruins(_) => EXPLOSION.len() * 2,
+            Animation::TurnBanner(_) => BANNER_FRAMES,
+        };
+        let mut rng = rand::rng();
+
+        for i in 0..frames {
+            let mut frame = self.frame()?;
+            // Too small to show the grid, so there's nothing to animate on.
+            let Some(layout) = Layout::for_size(frame.width, frame.height) else {
+                return Ok(false);
+            };
+
+            match animation {
+                Animation::Attack {
+                    source,
+                    target,
+                    attacker,
+                    defender,
+                    attack,
+                    defense,
+                } => {
+                    let landed = i >= ROLLING_FRAMES;
+                    let (attack_shown, defense_shown) = if landed {
+                        (attack.value, defense.value)
+                    } else {
+                        (
+                            rng.random_range(attack.min..=attack.max),
+                            rng.random_range(defense.min..=defense.max),
+                        )
+                    };
+                    let glyph = format!(" {} ", attacker.glyph());
+                    put_cell(
+                        &mut frame,
+                        layout,
+                        source,
+                        &glyph,
+                        theme.selection,
+                        theme.selection_text,
+                    );
+                    // The defender as it was, since the grid already shows how it ended.
+                    let glyph = format!(" {} ", defender.glyph());
+                    if landed && i % 2 == 0 {
+                        put_cell(
+                            &mut frame,
+                            layout,
+                            target,
+                            &glyph,
+                            theme.warning,
+                            theme.background,
+                        );
+                    } else {
+                        put_cell(
+                            &mut frame,
+                            layout,
+                            target,
+                            &glyph,
+                            theme.background,
+                            defender.color(),
+                        );
+                    }
+
+                    let screen_y = (layout.below_grid() + 8) as u16;
+                    let blank = " ".repeat(frame.width as usize);
+                    frame.put(0, screen_y, &blank, theme.background, theme.text);
+                    frame.put(
+                        0,
+                        screen_y,
+                        &format!(
+                            "{} rolls {:>2} against {} {:>2}",
+                            attacker, attack_shown, defender, defense_shown
+                        ),
+                        theme.background,
+                        if landed { theme.warning } else { theme.text },
+                    );
+                }
+                Animation::Ruins(pos) => {
+                    let sparks = if settings().unicode {
+                        EXPLOSION
+                    } else {
+                        ASCII_EXPLOSION
+                    };
+                    let spark = sparks[i / 2];
+                    put_cell(
+                        &mut frame,
+                        layout,
+                        pos,
+                        &format!("{}{}{}", spark, spark, spark),
+                        theme.warning,
+                        theme.ruins,
+                    );
+                }
+                Animation::TurnBanner(turn) => {
+                    let text = format!("{:^22}", format!("Turn {}", turn));
+                    let blank = " ".repeat(text.len());
+                    let screen_x = (GRID_X_OFFSET + (GRID_SIZE * 3 - text.len()) / 2) as u16;
+                    let screen_y = layout.grid_y(GRID_SIZE / 2);
+                    for (row, line) in [&blank, &text, &blank].into_iter().enumerate() {
+                        frame.put(
+                            screen_x,
+                            screen_y + row as u16 - 1,
+                            line,
+                            theme.selection,
+                            theme.selection_text,
+                        );
+                    }
+                }
+            }
+
+            present(frame)?;
+            if wait()? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+
+use crate::enums::*;
+use crate::rules::rules;
+use crate::structs::*;
+
+impl fmt::Display for Building {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Building::Walls => "Walls",
+            Building::Barracks => "Barracks",
+            Building::Market => "Market",
+            Building::Granary => "Granary",
+            Building::Watchtower => "Watchtower",
+            Building::Wonder => "Wonder",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl Building {
+    pub const ALL: [Building; 6] = [
+        Building::Walls,
+        Building::Barracks,
+        Building::Market,
+        Building::Granary,
+        Building::Watchtower,
+        Building::Wonder,
+    ];
+
+    pub fn cost(self) -> u32 {
+        let rules = rules();
+        match self {
+            Building::Walls => rules.walls_cost,
+            Building::Barracks => rules.barracks_cost,
+            Building::Market => rules.market_cost,
+            Building::Granary => rules.granary_cost,
+            Building::Watchtower => rules.watchtower_cost,
+            Building::Wonder => rules.wonder_cost,
+        }
+    }
+
+    pub fn defense_bonus(self) -> u32 {
+        match self {
+            Building::Walls => rules().walls_defense,
+            _ => 0,
+        }
+    }
+
+    pub fn production_bonus(self) -> u32 {
+        match self {
+            Building::Market => rules().market_production,
+            _ => 0,
+        }
+    }
+
+    // How far (in path distance) a city with this building can see enemy stats.
+    pub fn vision(self) -> u32 {
+        match self {
+            Building::Watchtower => rules().watchtower_vision,
+            _ => 0,
+        }
+    }
+
+    pub fn effect(self) -> String {
+        let rules = rules();
+        match self {
+            Building::Walls => format!("+{} to defense rolls.", rules.walls_defense),
+            Building::Barracks => format!(
+                "Combat upgrades cost {} per level instead of {}.",
+                rules.barracks_cost_per_level, rules.attack_upgrade_cost_per_level
+            ),
+            Building::Market => format!(
+                "+{} Resources every time the city produces.",
+                rules.market_production
+            ),
+            Building::Granary => format!(
+                "Production upgrades cost {} per level instead of {}.",
+                rules.granary_cost_per_level, rules.produce_upgrade_cost_per_level
+            ),
+            Building::Watchtower => "Reveals enemy cities nearby.".into(),
+            Building::Wonder => format!("Hold it for {} turns to win.", rules.wonder_hold_turns),
+        }
+    }
+}
+
+impl City {
+    pub fn has_building(&self, building: Building) -> bool {
+        self.buildings[building as usize]
+    }
+
+    pub fn buildings(&self) -> impl Iterator<Item = Building> + '_ {
+        Building::ALL
+            .into_iter()
+            .filter(|&building| self.has_building(building))
+    }
+
+    pub fn buildings_text(&self) -> String {
+        let names: Vec<String> = self.buildings().map(|b| b.to_string()).collect();
+        if names.is_empty() {
+            "None".into()
+        } else {
+            names.join(", ")
+        }
+    }
+
+    pub fn building_defense_bonus(&self) -> u32 {
+        self.buildings().map(Building::defense_bonus).sum()
+    }
+
+    pub fn building_production_bonus(&self) -> u32 {
+        self.buildings().map(Building::production_bonus).sum()
+    }
+
+    pub fn vision(&self) -> u32 {
+        self.buildings().map(Building::vision).max().unwrap_or(0)
+    }
+
+    pub fn build(&mut self, building: Building) -> Result<(), GameError> {
+        if self.has_building(building) {
+            return Err(GameError::AlreadyBuilt);
+        }
+        self.spend(building.cost())?;
+        self.buildings[building as usize] = true;
+        Ok(())
+    }
+}
+
+impl Game {
+    // Wonders only exist in games that can be won with one.
+    pub fn can_build(&self, building: Building) -> bool {
+        building != Building::Wonder || self.has_condition(VictoryCondition::Wonder)
+    }
+
+    // Whether any of `viewer`'s cities has a watchtower that can see `pos`.
+    pub fn is_revealed_to(&self, pos: TerminalPos, viewer: CityState) -> bool {
+        self.city_positions(Some(viewer))
+            .into_iter()
+            .any(|watcher| {
+                let Ok(city) = self.get_city_at_pos(watcher) else {
+                    return false;
+                };
+                city.vision() > 0
+                    && self
+                        .path_distance(watcher, pos)
+                        .is_some_and(|distance| distance <= city.vision())
+            })
+    }
+}
@@ -0,0 +1,165 @@
+use crate::enums::*;
+use crate::rules::rules;
+use crate::structs::*;
+
+impl City {
+    // Combat levels lost when an attack is repelled, which never goes below level 1.
+    fn repel_loss(&self) -> u32 {
+        let penalty = rules().failed_attack_penalty;
+        self.combat_level
+            .saturating_sub(self.combat_level.saturating_sub(penalty).max(1))
+    }
+}
+
+impl CombatPreview {
+    pub fn summary(&self) -> String {
+        format!(
+            "Rolls {}-{} vs {}-{}, expected loss {:.1} combat.",
+            self.attack.0, self.attack.1, self.defense.0, self.defense.1, self.expected_loss
+        )
+    }
+}
+
+// A chance as a whole percentage, for showing to the player.
+pub fn percent(chance: f64) -> String {
+    format!("{:.0}%", chance * 100.0)
+}
+
+impl Game {
+    // Terrain, technology and buildings all add to a defender's roll.
+    pub fn total_defense_bonus(&self, target: TerminalPos) -> Result<u32, GameError> {
+        let defender = self.get_city_at_pos(target)?;
+        Ok(self.get_cell_at_pos(target)?.terrain.defense_bonus()
+            + self.defense_bonus(defender.state)
+            + defender.building_defense_bonus())
+    }
+
+    // Counts every pair of rolls `resolve_attack` could make. Both are uniform,
+    // so each pair is equally likely.
+    pub fn combat_preview(
+        &self,
+        source: TerminalPos,
+        target: TerminalPos,
+        intent: AttackIntent,
+    ) -> Result<CombatPreview, GameError> {
+        let attacker = *self.get_source_city_at_pos(source)?;
+        let defender = *self.get_city_at_pos(target)?;
+        if defender.state == CityState::Destroyed || defender.state == attacker.state {
+            return Err(GameError::NoCityAtTarget);
+        }
+
+        let (attack_min, attack_max) = attacker.roll_range();
+        let attack_bonus = self.attack_bonus(attacker.state);
+        let (defense_min, defense_max) = defender.roll_range();
+        let defense_bonus = self.total_defense_bonus(target)?;
+        let margin = if intent == AttackIntent::Capture && attacker.state != CityState::Barbarian {
+            self.capture_margin(attacker.state)
+        } else {
+            0
+        };
+
+        let (mut success, mut held_out, mut repelled) = (0u64, 0u64, 0u64);
+        for attack in attack_min + attack_bonus..=attack_max + attack_bonus {
+            for defense in defense_min + defense_bonus..=defense_max + defense_bonus {
+                if attack <= defense {
+                    repelled += 1;
+                } else if attack <= defense + margin {
+                    held_out += 1;
+                } else {
+                    success += 1;
+                }
+            }
+        }
+
+        let total = (success + held_out + repelled) as f64;
+        let repelled = repelled as f64 / total;
+        Ok(CombatPreview {
+            attack: (attack_min + attack_bonus, attack_max + attack_bonus),
+            defense: (defense_min + defense_bonus, defense_max + defense_bonus),
+            success: success as f64 / total,
+            held_out: held_out as f64 / total,
+            repelled,
+            expected_loss: repelled * attacker.repel_loss() as f64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn city(game: &mut Game, x: u16, state: CityState, combat_level: u32) -> TerminalPos {
+        let pos = TerminalPos(x, 0);
+        game.get_mut_cell_at_pos(pos).unwrap().city = Some(City {
+            state,
+            combat_level,
+            ..City::default()
+        });
+        pos
+    }
+
+    fn assert_chance(chance: f64, expected: f64) {
+        assert!(
+            (chance - expected).abs() < 1e-9,
+            "{} != {}",
+            chance,
+            expected
+        );
+    }
+
+    #[test]
+    fn an_even_fight_counts_every_pair_of_rolls() {
+        let mut game = Game::default();
+        let source = city(&mut game, 0, CityState::OwnedByPlayer, 5);
+        let target = city(&mut game, 1, CityState::OwnedByComputer, 5);
+
+        // Both roll 1-6: 21 of the 36 pairs are ties or worse, and 5 win by
+        // exactly the capture margin.
+        let capture = game
+            .combat_preview(source, target, AttackIntent::Capture)
+            .unwrap();
+        assert_eq!(capture.attack, (1, 6));
+        assert_eq!(capture.defense, (1, 6));
+        assert_chance(capture.success, 10.0 / 36.0);
+        assert_chance(capture.held_out, 5.0 / 36.0);
+        assert_chance(capture.repelled, 21.0 / 36.0);
+        assert_chance(capture.expected_loss, 21.0 / 36.0);
+
+        let raze = game
+            .combat_preview(source, target, AttackIntent::Raze)
+            .unwrap();
+        assert_chance(raze.success, 15.0 / 36.0);
+        assert_chance(raze.held_out, 0.0);
+    }
+
+    #[test]
+    fn terrain_adds_to_the_defense() {
+        let mut game = Game::default();
+        let source = city(&mut game, 0, CityState::OwnedByPlayer, 1);
+        let target = city(&mut game, 1, CityState::OwnedByComputer, 1);
+        game.get_mut_cell_at_pos(target).unwrap().terrain = Terrain::Mountain;
+
+        let preview = game
+            .combat_preview(source, target, AttackIntent::Raze)
+            .unwrap();
+        assert_eq!(preview.defense, (3, 4));
+        assert_chance(preview.repelled, 1.0);
+        // A level 1 city has nothing left to lose.
+        assert_chance(preview.expected_loss, 0.0);
+    }
+
+    #[test]
+    fn only_other_sides_cities_can_be_attacked() {
+        let mut game = Game::default();
+        let source = city(&mut game, 0, CityState::OwnedByPlayer, 5);
+        let own = city(&mut game, 1, CityState::OwnedByPlayer, 5);
+        let ruins = city(&mut game, 2, CityState::Destroyed, 5);
+
+        for target in [own, ruins, TerminalPos(3, 0)] {
+            assert_eq!(
+                game.combat_preview(source, target, AttackIntent::Capture),
+                Err(GameError::NoCityAtTarget)
+            );
+        }
+    }
+}
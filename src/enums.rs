@@ -1,23 +1,46 @@
-use crate::structs::TerminalPos;
+use crate::structs::{Roll, TerminalPos};
 use std::io;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum CityState {
     OwnedByPlayer,
     OwnedByComputer,
+    Neutral,   // Never acts, but defends itself and can be captured.
+    Barbarian, // Raids nearby cities every few turns.
     Destroyed,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Terrain {
+    #[default]
+    Plains,
+    Forest,
+    Mountain,
+    River,
+    ResourceDeposit,
+    Wall,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
 pub enum InputOutcome {
     #[default]
     None,
     Redraw,
+    // A cell was clicked, and the cursor moved onto it.
+    Select,
     Interact,
     EndTurn,
+    TechTree,
     QuitGame,
 }
 
+// Compact squeezes the grid to one screen row per cell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Layout {
+    Regular,
+    Compact,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
 pub enum GameState {
     #[default]
@@ -27,6 +50,92 @@ pub enum GameState {
     PlayerWon,
     ComputerWon,
     Stalemate,
+    Abandoned,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VictoryCondition {
+    Conquest,
+    Score,
+    Domination,
+    Economic,
+    Wonder,
+    KingOfTheHill,
+}
+
+// What the grid highlights around the city under the cursor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Overlay {
+    #[default]
+    None,
+    Range,
+    Threats,
+    Reach,
+}
+
+// What an overlay says about one cell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OverlayMark {
+    AttackRange,
+    BuildRange,
+    Threat,
+    Reachable,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ThemeName {
+    #[default]
+    Default,
+    HighContrast,
+    Deuteranopia,
+    Protanopia,
+    Monochrome,
+}
+
+// How many colors the terminal can show, from none at all to 24-bit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    None,
+    Basic,
+    Extended,
+    TrueColor,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GameMode {
+    Skirmish,
+    Campaign,
+    Tutorial,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TutorialStep {
+    MoveCursor,
+    Produce,
+    EndTurn,
+    Upgrade,
+    FoundCity,
+    DestroyWall,
+    Attack,
+}
+
+// Something a scenario makes happen at the end of a given turn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TriggerEffect {
+    Reinforcements {
+        owner: CityState,
+        position: TerminalPos,
+        level: u32,
+    },
+    Message(String),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WorldEvent {
+    Plague,
+    Harvest,
+    Earthquake,
+    Rebellion,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
@@ -48,6 +157,18 @@ pub enum GameError {
     TargetIsSource,
     NotEnoughResources,
     NeedTargetPosition,
+    TargetNotReachable,
+    TargetOutOfRange,
+    NotYourCity,
+    CityAlreadyActed,
+    CannotCapture,
+    TechAlreadyKnown,
+    TechLocked,
+    AlreadyResearching,
+    AlreadyBuilt,
+    BuildingUnavailable,
+    ActionDisabled,
+    NoTargetInRange,
     IO(io::ErrorKind),
 }
 
@@ -59,10 +180,24 @@ pub enum RawCommand {
     MoveDown,
     Interact,
     EndTurn,
+    OpenTechTree,
+    NextCity,
+    CycleOverlay,
     QuitGame,
     None,
 }
 
+// What an action has to be pointed at.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TargetKind {
+    None,
+    EnemyCity,
+    CapturableCity,
+    Obstacle,
+    EmptyTile,
+    OwnCity,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum GameActionSkeleton {
     Produce,
@@ -70,7 +205,52 @@ pub enum GameActionSkeleton {
     UpgradeProduce,
     DestroyWall,
     AttackCity,
+    CaptureCity,
     GenerateCity,
+    TransferResources,
+    SendConvoy,
+    Build(Building),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Building {
+    Walls,
+    Barracks,
+    Market,
+    Granary,
+    Watchtower,
+    Wonder,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Tech {
+    Agriculture,
+    Masonry,
+    Fortifications,
+    Logistics,
+    Siege,
+}
+
+// Something the engine did that's worth showing as it happens, rather than
+// just as a status line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Animation {
+    Attack {
+        source: TerminalPos,
+        target: TerminalPos,
+        attacker: CityState,
+        defender: CityState,
+        attack: Roll,
+        defense: Roll,
+    },
+    Ruins(TerminalPos),
+    TurnBanner(u32),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AttackIntent {
+    Raze,    // Turn the city into ruins.
+    Capture, // Take the city over. Needs a bigger margin to pull off.
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -91,9 +271,22 @@ pub enum GameAction {
     AttackCity {
         source: TerminalPos,
         target: TerminalPos,
+        intent: AttackIntent,
     },
     GenerateCity {
         source: TerminalPos,
         target: TerminalPos,
     },
+    TransferResources {
+        source: TerminalPos,
+        target: TerminalPos,
+    },
+    SendConvoy {
+        source: TerminalPos,
+        target: TerminalPos,
+    },
+    Build {
+        source: TerminalPos,
+        building: Building,
+    },
 }
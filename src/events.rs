@@ -0,0 +1,201 @@
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+use crate::enums::*;
+use crate::rules::rules;
+use crate::shared::*;
+use crate::structs::*;
+
+impl fmt::Display for WorldEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            WorldEvent::Plague => "Plague",
+            WorldEvent::Harvest => "Bountiful Harvest",
+            WorldEvent::Earthquake => "Earthquake",
+            WorldEvent::Rebellion => "Rebellion",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl WorldEvent {
+    pub const ALL: [WorldEvent; 4] = [
+        WorldEvent::Plague,
+        WorldEvent::Harvest,
+        WorldEvent::Earthquake,
+        WorldEvent::Rebellion,
+    ];
+}
+
+impl Game {
+    // The same seed and turn always give the same events, so a game can be replayed.
+    fn event_rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.event_seed.wrapping_add(u64::from(self.turn)))
+    }
+
+    // Rolls for a world event at the end of a round and applies it.
+    pub fn world_events(&mut self) {
+        let mut rng = self.event_rng();
+        if !rng.random_bool(self.difficulty.event_chance()) {
+            return;
+        }
+
+        let weights = rules().event_weights(self.difficulty);
+        let events: Vec<(WorldEvent, u32)> = WorldEvent::ALL.into_iter().zip(weights).collect();
+        let Ok(&(event, _)) = events.choose_weighted(&mut rng, |&(_, weight)| weight) else {
+            return;
+        };
+        match event {
+            WorldEvent::Plague => self.plague(&mut rng),
+            WorldEvent::Harvest => self.harvest(&mut rng),
+            WorldEvent::Earthquake => self.earthquake(&mut rng),
+            WorldEvent::Rebellion => self.rebellion(&mut rng),
+        }
+    }
+
+    fn faction_cities(&self) -> Vec<TerminalPos> {
+        self.city_positions(None)
+            .into_iter()
+            .filter(|&pos| {
+                self.get_city_at_pos(pos).is_ok_and(|city| {
+                    matches!(
+                        city.state,
+                        CityState::OwnedByPlayer | CityState::OwnedByComputer
+                    )
+                })
+            })
+            .collect()
+    }
+
+    fn plague(&mut self, rng: &mut StdRng) {
+        let Some(&pos) = self.faction_cities().choose(rng) else {
+            return;
+        };
+        let Ok(city) = self.get_mut_city_at_pos(pos) else {
+            return;
+        };
+        city.generation_level = city
+            .generation_level
+            .saturating_sub(rules().plague_production_loss)
+            .max(1);
+        let owner = city.state;
+
+        if owner == CityState::OwnedByPlayer {
+            self.status = Some("Plague struck one of your cities!");
+        }
+        self.log_event(format!(
+            "{}: plague broke out in the {} city at {}.",
+            WorldEvent::Plague,
+            owner,
+            pos
+        ));
+    }
+
+    fn harvest(&mut self, rng: &mut StdRng) {
+        let Some(&pos) = self.faction_cities().choose(rng) else {
+            return;
+        };
+        let Ok(city) = self.get_mut_city_at_pos(pos) else {
+            return;
+        };
+        city.resources += rules().harvest_resources;
+        let owner = city.state;
+
+        if owner == CityState::OwnedByPlayer {
+            self.status = Some("One of your cities had a bountiful harvest!");
+        }
+        self.log_event(format!(
+            "{}: the {} city at {} gained {} Resources.",
+            WorldEvent::Harvest,
+            owner,
+            pos,
+            rules().harvest_resources
+        ));
+    }
+
+    // Knocks down a wall or raises a new one, never cutting the map in two,
+    // walling off the Hill or dropping a wall onto a convoy's road.
+    fn earthquake(&mut self, rng: &mut StdRng) {
+        let hill = self.hill();
+        let mut cells: Vec<TerminalPos> = (0..GRID_SIZE)
+            .flat_map(|y| (0..GRID_SIZE).map(move |x| TerminalPos(x as u16, y as u16)))
+            .filter(|&pos| {
+                hill != Some(pos)
+                    && self
+                        .get_cell_at_pos(pos)
+                        .is_ok_and(|cell| cell.city.is_none())
+                    && !self
+                        .convoys
+                        .iter()
+                        .any(|convoy| convoy.position == pos || convoy.route.contains(&pos))
+            })
+            .collect();
+        cells.shuffle(rng);
+
+        for pos in cells {
+            let Ok(cell) = self.get_mut_cell_at_pos(pos) else {
+                continue;
+            };
+            let previous = cell.terrain;
+
+            if previous == Terrain::Wall {
+                cell.terrain = Terrain::Plains;
+                self.log_event(format!(
+                    "{}: the wall at {} collapsed.",
+                    WorldEvent::Earthquake,
+                    pos
+                ));
+            } else {
+                cell.terrain = Terrain::Wall;
+                if !self.is_map_connected() || !self.is_hill_reachable() {
+                    if let Ok(cell) = self.get_mut_cell_at_pos(pos) {
+                        cell.terrain = previous;
+                    }
+                    continue;
+                }
+                self.log_event(format!(
+                    "{}: rubble piled up into a wall at {}.",
+                    WorldEvent::Earthquake,
+                    pos
+                ));
+            }
+
+            self.status = Some("An earthquake reshaped the land!");
+            return;
+        }
+    }
+
+    // A poorly defended city breaks away, but never a faction's last one.
+    fn rebellion(&mut self, rng: &mut StdRng) {
+        let candidates: Vec<TerminalPos> = self
+            .faction_cities()
+            .into_iter()
+            .filter(|&pos| {
+                self.get_city_at_pos(pos).is_ok_and(|city| {
+                    city.combat_level <= rules().rebellion_max_combat_level
+                        && self.city_positions(Some(city.state)).len() > 1
+                })
+            })
+            .collect();
+        let Some(&pos) = candidates.choose(rng) else {
+            return;
+        };
+        let Ok(city) = self.get_mut_city_at_pos(pos) else {
+            return;
+        };
+        let owner = city.state;
+        city.state = CityState::Neutral;
+
+        if owner == CityState::OwnedByPlayer {
+            self.status = Some("One of your cities rebelled and declared independence!");
+        }
+        self.log_event(format!(
+            "{}: the {} city at {} declared independence.",
+            WorldEvent::Rebellion,
+            owner,
+            pos
+        ));
+    }
+}
@@ -1,9 +1,15 @@
 use crate::enums::*;
+use crate::keymap::keymap;
+use crate::overlay::overlay_glyphs;
+use crate::render::{draw_too_small, present};
+use crate::rules::rules;
+use crate::settings::settings;
 use crate::shared::*;
 use crate::structs::*;
+use crate::theme::theme;
 use crossterm::{
-    cursor, execute,
-    style::{Color, Print},
+    cursor, event, execute,
+    style::Color,
     terminal::{self},
 };
 use inquire::{self};
@@ -17,7 +23,12 @@ use std::{
 impl Drop for GameGuard {
     fn drop(&mut self) {
         let _ = terminal::disable_raw_mode();
-        let _ = execute!(stdout(), terminal::LeaveAlternateScreen, cursor::Show);
+        let _ = execute!(
+            stdout(),
+            event::DisableMouseCapture,
+            terminal::LeaveAlternateScreen,
+            cursor::Show
+        );
     }
 }
 
@@ -47,14 +58,97 @@ impl fmt::Display for GameActionSkeleton {
             GameActionSkeleton::Produce => "Produce Resources",
             GameActionSkeleton::UpgradeAttack => "Upgrade Combat Readiness Level",
             GameActionSkeleton::UpgradeProduce => "Upgrade Production Level",
-            GameActionSkeleton::AttackCity => "Attack City",
-            GameActionSkeleton::DestroyWall => "Destroy Wall",
+            GameActionSkeleton::AttackCity => "Attack City (Raze)",
+            GameActionSkeleton::CaptureCity => "Attack City (Capture)",
+            GameActionSkeleton::DestroyWall => "Destroy Wall / Clear Ruins",
             GameActionSkeleton::GenerateCity => "Build New City",
+            GameActionSkeleton::TransferResources => {
+                "Transfer All Resources (Instant, Costs Distance)"
+            }
+            GameActionSkeleton::SendConvoy => "Send All Resources by Convoy (Slow, Free)",
+            GameActionSkeleton::Build(building) => {
+                return write!(f, "Build {} - {}", building, building.effect());
+            }
         };
         write!(f, "{}", text)
     }
 }
 
+impl fmt::Display for CityState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            CityState::OwnedByPlayer => "Player",
+            CityState::OwnedByComputer => "Computer",
+            CityState::Neutral => "Neutral",
+            CityState::Barbarian => "Barbarian",
+            CityState::Destroyed => "Ruined",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl CityState {
+    pub fn glyph(self) -> char {
+        match self {
+            CityState::OwnedByPlayer => 'P',
+            CityState::OwnedByComputer => 'C',
+            CityState::Neutral => 'N',
+            CityState::Barbarian => 'B',
+            CityState::Destroyed => 'x',
+        }
+    }
+
+    // Convoys use the owner's letter in lower case.
+    pub fn convoy_glyph(self) -> char {
+        self.glyph().to_ascii_lowercase()
+    }
+
+    pub fn color(self) -> Color {
+        let theme = theme();
+        match self {
+            CityState::OwnedByPlayer => theme.player,
+            CityState::OwnedByComputer => theme.computer,
+            CityState::Neutral => theme.neutral,
+            CityState::Barbarian => theme.barbarian,
+            CityState::Destroyed => theme.ruins,
+        }
+    }
+}
+
+impl fmt::Display for TerminalPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.0, self.1)
+    }
+}
+
+impl GameError {
+    pub fn reason(self) -> &'static str {
+        match self {
+            GameError::NotValidPosition => "That's not on the map.",
+            GameError::NoCityAtTarget => "There's no city to target there.",
+            GameError::NoCityAtSource => "There's no city to act from.",
+            GameError::NoWallAtTarget => "There's no wall or ruin there.",
+            GameError::TargetIsOccupied => "That tile is already occupied.",
+            GameError::TargetIsSource => "A city cannot target itself.",
+            GameError::NotEnoughResources => "Not enough resources!",
+            GameError::NeedTargetPosition => "That action needs a target.",
+            GameError::TargetNotReachable => "There's no path to that tile.",
+            GameError::TargetOutOfRange => "That tile is too far away.",
+            GameError::NotYourCity => "You cannot act on an opposing city!",
+            GameError::CityAlreadyActed => "That city has already acted this turn.",
+            GameError::CannotCapture => "That can't be captured, only destroyed.",
+            GameError::TechAlreadyKnown => "You already know that technology.",
+            GameError::TechLocked => "You need to research its prerequisite first.",
+            GameError::AlreadyResearching => "You're already researching something.",
+            GameError::AlreadyBuilt => "That city already has one of those.",
+            GameError::BuildingUnavailable => "That can't be built in this game.",
+            GameError::ActionDisabled => "That isn't allowed in this game.",
+            GameError::NoTargetInRange => "There's nothing in range to target.",
+            GameError::IO(_) => "Something went wrong with the terminal.",
+        }
+    }
+}
+
 impl From<std::io::Error> for GameError {
     fn from(err: Error) -> Self {
         GameError::IO(err.kind())
@@ -93,42 +187,213 @@ impl TryFrom<(GameActionSkeleton, TerminalPos, Option<TerminalPos>)> for GameAct
             GameActionSkeleton::AttackCity => GameAction::AttackCity {
                 source,
                 target: require_target(source, target)?,
+                intent: AttackIntent::Raze,
+            },
+            GameActionSkeleton::CaptureCity => GameAction::AttackCity {
+                source,
+                target: require_target(source, target)?,
+                intent: AttackIntent::Capture,
             },
             GameActionSkeleton::GenerateCity => GameAction::GenerateCity {
                 source,
                 target: require_target(source, target)?,
             },
+            GameActionSkeleton::TransferResources => GameAction::TransferResources {
+                source,
+                target: require_target(source, target)?,
+            },
+            GameActionSkeleton::SendConvoy => GameAction::SendConvoy {
+                source,
+                target: require_target(source, target)?,
+            },
+            GameActionSkeleton::Build(building) => GameAction::Build { source, building },
         })
     }
 }
 
+impl GameAction {
+    pub fn source(&self) -> TerminalPos {
+        match *self {
+            GameAction::Produce { source }
+            | GameAction::UpgradeAttack { source }
+            | GameAction::UpgradeProduce { source }
+            | GameAction::DestroyWall { source, .. }
+            | GameAction::AttackCity { source, .. }
+            | GameAction::GenerateCity { source, .. }
+            | GameAction::TransferResources { source, .. }
+            | GameAction::SendConvoy { source, .. }
+            | GameAction::Build { source, .. } => source,
+        }
+    }
+}
+
+impl fmt::Display for Terrain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Terrain::Plains => "Plains",
+            Terrain::Forest => "Forest",
+            Terrain::Mountain => "Mountain",
+            Terrain::River => "River",
+            Terrain::ResourceDeposit => "Resource Deposit",
+            Terrain::Wall => "Wall",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl Terrain {
+    pub fn glyph(self) -> char {
+        let unicode = settings().unicode;
+        match self {
+            Terrain::Plains => '.',
+            Terrain::Forest if unicode => '♣',
+            Terrain::Forest => 'f',
+            Terrain::Mountain if unicode => '▲',
+            Terrain::Mountain => '^',
+            Terrain::River => '~',
+            Terrain::ResourceDeposit => '$',
+            Terrain::Wall => '#',
+        }
+    }
+
+    pub fn color(self) -> Color {
+        let theme = theme();
+        match self {
+            Terrain::Plains => theme.plains,
+            Terrain::Forest => theme.forest,
+            Terrain::Mountain => theme.mountain,
+            Terrain::River => theme.river,
+            Terrain::ResourceDeposit => theme.deposit,
+            Terrain::Wall => theme.wall,
+        }
+    }
+
+    pub fn production_bonus(self) -> u32 {
+        let rules = rules();
+        match self {
+            Terrain::Forest => rules.forest_production,
+            Terrain::River => rules.river_production,
+            Terrain::ResourceDeposit => rules.deposit_production,
+            _ => 0,
+        }
+    }
+
+    pub fn defense_bonus(self) -> u32 {
+        let rules = rules();
+        match self {
+            Terrain::Forest => rules.forest_defense,
+            Terrain::Mountain => rules.mountain_defense,
+            _ => 0,
+        }
+    }
+
+    // How many range points it takes to step onto this terrain. None if you
+    // can't walk through it at all.
+    pub fn movement_cost(self) -> Option<u32> {
+        let rules = rules();
+        match self {
+            Terrain::Plains => Some(rules.plains_movement),
+            Terrain::Forest => Some(rules.forest_movement),
+            Terrain::Mountain => Some(rules.mountain_movement),
+            Terrain::River => Some(rules.river_movement),
+            Terrain::ResourceDeposit => Some(rules.deposit_movement),
+            Terrain::Wall => None,
+        }
+    }
+
+    pub fn is_buildable(self) -> bool {
+        !matches!(self, Terrain::River | Terrain::Wall)
+    }
+
+    pub fn effects(self) -> String {
+        let mut text = String::new();
+        if let Some(cost) = self.movement_cost() {
+            text += &format!("\r\n\t- Movement cost: {}", cost);
+        }
+        if self.production_bonus() > 0 {
+            text += &format!("\r\n\t- Production bonus: +{}", self.production_bonus());
+        }
+        if self.defense_bonus() > 0 {
+            text += &format!("\r\n\t- Defense bonus: +{}", self.defense_bonus());
+        }
+        if !self.is_buildable() {
+            text += "\r\n\t- Cannot build a city here.";
+        }
+        text
+    }
+}
+
 impl Cell {
-    pub fn info(&self) -> String {
-        if self.blocked {
-            return "Wall.\r\n\t- Cannot build a city here.\r\n\t- Can be destroyed for 10 Resources.".into();
+    pub fn is_wall(&self) -> bool {
+        self.terrain == Terrain::Wall
+    }
+
+    // `revealed` shows the stats of enemy cities, e.g. when a watchtower sees them.
+    pub fn info(&self, revealed: bool) -> String {
+        if self.is_wall() {
+            return format!(
+                "Wall.\r\n\t- Cannot build a city here.\r\n\t- Can be destroyed for {} Resources.",
+                rules().wall_destroy_cost
+            );
         }
 
         if self.city.is_none() {
-            return "Empty tile.".into();
+            return format!("{}.{}", self.terrain, self.terrain.effects());
         }
 
         let city = self.city.unwrap();
 
         match city.state {
             CityState::Destroyed => {
-                "A destroyed city.\r\n\t- It is, effectively, now just an obstacle.\r\n\t- Can be cleaned up for 5 Resources, turning it into an empty tile.\r\n\t- Probably has SOME use case...".into()
+                format!(
+                    "A destroyed city.\r\n\t- It is, effectively, now just an obstacle.\r\n\t- Can be cleaned up for {} Resources, turning it into an empty tile.\r\n\t- Probably has SOME use case...",
+                    rules().ruins_clear_cost
+                )
             }
             CityState::OwnedByPlayer => {
                 format!(
-                    "Your city.\r\n\t- Productivity Level: {}\r\n\t- Combat Readiness Level: {}\r\n\t- Resources: {}\r\n\t- Overall Power: {}",
+                    "Your city.\r\n\t- Productivity Level: {}\r\n\t- Combat Readiness Level: {}\r\n\t- Resources: {}\r\n\t- Overall Power: {}\r\n\t- Terrain: {}\r\n\t- Buildings: {}",
                     city.generation_level,
                     city.combat_level,
                     city.resources,
-                    city.get_power()
+                    city.get_power(),
+                    self.terrain,
+                    city.buildings_text()
+                )
+            }
+            CityState::OwnedByComputer if revealed => {
+                format!(
+                    "Enemy city (spotted by your watchtower).\r\n\t- Productivity Level: {}\r\n\t- Combat Readiness Level: {}\r\n\t- Resources: {}\r\n\t- Overall Power: {}\r\n\t- Terrain: {}\r\n\t- Buildings: {}",
+                    city.generation_level,
+                    city.combat_level,
+                    city.resources,
+                    city.get_power(),
+                    self.terrain,
+                    city.buildings_text()
                 )
             }
             CityState::OwnedByComputer => {
-                "Enemy city.\r\n\t- Statistics unknown.".into()
+                format!(
+                    "Enemy city.\r\n\t- Statistics unknown.\r\n\t- Terrain: {}",
+                    self.terrain
+                )
+            }
+            CityState::Neutral => {
+                format!(
+                    "Neutral city.\r\n\t- Does not act, but will defend itself.\r\n\t- Combat Readiness Level: {}\r\n\t- Capture it for {} Resources.\r\n\t- Terrain: {}",
+                    city.combat_level,
+                    rules().neutral_capture_bonus,
+                    self.terrain
+                )
+            }
+            CityState::Barbarian => {
+                format!(
+                    "Barbarian camp.\r\n\t- Raids a nearby city every {} turns.\r\n\t- Combat Readiness Level: {}\r\n\t- Destroy it to claim its loot plus {} Resources.\r\n\t- Terrain: {}",
+                    rules().barbarian_raid_interval,
+                    city.combat_level,
+                    rules().barbarian_camp_bounty,
+                    self.terrain
+                )
             }
         }
     }
@@ -136,34 +401,76 @@ impl Cell {
 
 impl GameDifficulty {
     pub fn block_chance(self) -> f64 {
-        match self {
-            GameDifficulty::Easy => 0.05,
-            GameDifficulty::Standard => 0.10,
-            GameDifficulty::Hard => 0.25,
-            GameDifficulty::NotEvenRemotelyFair => 0.30,
-        }
+        let rules = rules();
+        let percent = match self {
+            GameDifficulty::Easy => rules.easy_wall_chance_percent,
+            GameDifficulty::Standard => rules.standard_wall_chance_percent,
+            GameDifficulty::Hard => rules.hard_wall_chance_percent,
+            GameDifficulty::NotEvenRemotelyFair => rules.unfair_wall_chance_percent,
+        };
+        f64::from(percent) / 100.0
     }
 
     pub fn starting_resources(self) -> u32 {
+        let rules = rules();
         match self {
-            GameDifficulty::Easy => 10,
-            GameDifficulty::Standard => 3,
-            _ => 0,
+            GameDifficulty::Easy => rules.easy_starting_resources,
+            GameDifficulty::Standard => rules.standard_starting_resources,
+            GameDifficulty::Hard => rules.hard_starting_resources,
+            GameDifficulty::NotEvenRemotelyFair => rules.unfair_starting_resources,
         }
     }
 
     pub fn starting_enemy_level(self) -> u32 {
+        let rules = rules();
         match self {
-            GameDifficulty::Hard => 2,
-            GameDifficulty::NotEvenRemotelyFair => 5,
-            _ => 1,
+            GameDifficulty::Easy => rules.easy_starting_enemy_level,
+            GameDifficulty::Standard => rules.standard_starting_enemy_level,
+            GameDifficulty::Hard => rules.hard_starting_enemy_level,
+            GameDifficulty::NotEvenRemotelyFair => rules.unfair_starting_enemy_level,
         }
     }
 
+    pub fn neutral_city_count(self) -> usize {
+        let rules = rules();
+        let count = match self {
+            GameDifficulty::Easy => rules.easy_neutral_cities,
+            GameDifficulty::Standard => rules.standard_neutral_cities,
+            GameDifficulty::Hard => rules.hard_neutral_cities,
+            GameDifficulty::NotEvenRemotelyFair => rules.unfair_neutral_cities,
+        };
+        count as usize
+    }
+
+    pub fn barbarian_camp_count(self) -> usize {
+        let rules = rules();
+        let count = match self {
+            GameDifficulty::Easy => rules.easy_barbarian_camps,
+            GameDifficulty::Standard => rules.standard_barbarian_camps,
+            GameDifficulty::Hard => rules.hard_barbarian_camps,
+            GameDifficulty::NotEvenRemotelyFair => rules.unfair_barbarian_camps,
+        };
+        count as usize
+    }
+
+    pub fn event_chance(self) -> f64 {
+        let rules = rules();
+        let percent = match self {
+            GameDifficulty::Easy => rules.easy_event_chance_percent,
+            GameDifficulty::Standard => rules.standard_event_chance_percent,
+            GameDifficulty::Hard => rules.hard_event_chance_percent,
+            GameDifficulty::NotEvenRemotelyFair => rules.unfair_event_chance_percent,
+        };
+        f64::from(percent) / 100.0
+    }
+
     pub fn starting_player_level(self) -> u32 {
+        let rules = rules();
         match self {
-            GameDifficulty::Easy => 2,
-            _ => 1,
+            GameDifficulty::Easy => rules.easy_starting_player_level,
+            GameDifficulty::Standard => rules.standard_starting_player_level,
+            GameDifficulty::Hard => rules.hard_starting_player_level,
+            GameDifficulty::NotEvenRemotelyFair => rules.unfair_starting_player_level,
         }
     }
 }
@@ -191,92 +498,167 @@ impl Game {
         }
     }
 
-    pub fn draw_cell_info(&self) -> io::Result<()> {
-        let screen_y = ((GRID_Y_OFFSET + GRID_SIZE * 2) + 1) as u16;
+    pub fn draw_cell_info(&self, frame: &mut Frame, layout: Layout) {
+        let screen_y = (layout.below_grid() + 1) as u16;
 
         let x = self.cursor_loc.0 as usize;
         let y = self.cursor_loc.1 as usize;
 
         let Some(cell) = self.grid.get(y).and_then(|row| row.get(x)) else {
-            return Ok(());
+            return;
         };
-        move_cursor(0, screen_y)?;
 
-        execute!(stdout(), Print(cell.info()))?;
-        Ok(())
+        let revealed = self.is_revealed_to(self.cursor_loc, CityState::OwnedByPlayer);
+        let mut text = cell.info(revealed);
+
+        if let Some(convoy) = self.convoy_at(self.cursor_loc) {
+            text += &if convoy.owner == CityState::OwnedByPlayer {
+                format!(
+                    "\r\n\t- Your convoy is here, carrying {} Resources.",
+                    convoy.amount
+                )
+            } else {
+                format!("\r\n\t- A {} convoy is passing through.", convoy.owner)
+            };
+        }
+
+        if self.hill() == Some(self.cursor_loc) {
+            text += &format!(
+                "\r\n\t- The Hill: hold a city here for {} turns to win.",
+                rules().hill_hold_turns
+            );
+        }
+
+        for (i, line) in text.split("\r\n").enumerate() {
+            frame.put(
+                0,
+                screen_y + i as u16,
+                &line.replace('\t', "        "),
+                Color::Reset,
+                Color::Reset,
+            );
+        }
     }
 
-    pub fn draw_instructions(&self) -> io::Result<()> {
-        let screen_y = (GRID_Y_OFFSET + GRID_SIZE * 2) as u16;
-        move_cursor(0, screen_y)?;
-        print_with_color(
-            "[↑↓←→ to move, enter to interact, s to end turn, ESC to quit]",
-            Color::Black,
-            Color::Cyan,
-        )?;
-        Ok(())
+    pub fn draw_status(&self, frame: &mut Frame, layout: Layout) {
+        let Some(status) = self.status else {
+            return;
+        };
+
+        let screen_y = (layout.below_grid() + 8) as u16;
+        let theme = theme();
+        frame.put(0, screen_y, status, theme.background, theme.warning);
+    }
+
+    pub fn draw_log(&self, frame: &mut Frame, layout: Layout) {
+        let screen_y = layout.below_grid() + 9;
+        let shown = self.log.len().saturating_sub(LOG_LINES_SHOWN);
+        let theme = theme();
+
+        for (i, entry) in self.log[shown..].iter().enumerate() {
+            frame.put(
+                0,
+                (screen_y + i) as u16,
+                entry,
+                theme.background,
+                theme.faint,
+            );
+        }
     }
 
-    pub fn draw_grid_text(&self) -> io::Result<()> {
+    pub fn draw_instructions(&self, frame: &mut Frame, layout: Layout) {
+        let screen_y = layout.below_grid() as u16;
+        frame.put(
+            0,
+            screen_y,
+            &keymap().instructions(),
+            theme().background,
+            theme().accent,
+        );
+    }
+
+    pub fn draw_grid_text(&self, frame: &mut Frame, layout: Layout) {
         for (y, _) in self.grid.iter().enumerate() {
-            let screen_y = (GRID_Y_OFFSET + y * 2) as u16;
-            move_cursor(0, screen_y)?;
-            print!("{}", y);
+            let screen_y = layout.grid_y(y);
+            frame.put(0, screen_y, &y.to_string(), Color::Reset, Color::Reset);
         }
 
         if let Some(first_row) = self.grid.first() {
             for (x, _) in first_row.iter().enumerate() {
                 let screen_x = ((GRID_X_OFFSET + 1) + x * 3) as u16;
-                move_cursor(screen_x, 0)?;
-                print!("{}", x);
+                frame.put(screen_x, 0, &x.to_string(), Color::Reset, Color::Reset);
             }
         }
-
-        Ok(())
     }
 
-    pub fn draw_grid(&self) -> io::Result<()> {
+    pub fn draw_grid(&self, frame: &mut Frame, layout: Layout) {
+        let theme = theme();
+        let marks = self.overlay_marks();
+        let glyphs = overlay_glyphs();
         for (y, row) in self.grid.iter().enumerate() {
-            let screen_y = (GRID_Y_OFFSET + y * 2) as u16;
+            let screen_y = layout.grid_y(y);
 
             for (x, cell) in row.iter().enumerate() {
                 let screen_x = (GRID_X_OFFSET + x * 3) as u16;
-                move_cursor(screen_x, screen_y)?;
 
-                let symbol = if cell.blocked {
-                    " # "
-                } else {
-                    match cell.city.as_ref().map(|c| c.state) {
-                        None => " . ",
-                        Some(CityState::Destroyed) => " x ",
-                        Some(CityState::OwnedByPlayer) => " P ",
-                        Some(_) => " C ",
-                    }
+                let pos = TerminalPos(x as u16, y as u16);
+                let (symbol, color) = match (cell.city, self.convoy_at(pos)) {
+                    (Some(city), _) => (city.state.glyph(), city.state.color()),
+                    (None, Some(convoy)) => (convoy.owner.convoy_glyph(), convoy.owner.color()),
+                    (None, None) => (cell.terrain.glyph(), cell.terrain.color()),
                 };
-
-                let color = if cell.blocked {
-                    Color::Grey
+                let mark = marks[y][x];
+                let background = if let Some(mark) = mark.filter(|_| !glyphs) {
+                    mark.tint()
+                } else if self.hill() == Some(pos) {
+                    theme.hill
                 } else {
-                    match cell.city.as_ref().map(|c| c.state) {
-                        None => Color::Grey,
-                        Some(CityState::Destroyed) => Color::DarkRed,
-                        Some(CityState::OwnedByPlayer) => Color::Cyan,
-                        Some(_) => Color::Red,
-                    }
+                    theme.background
                 };
 
-                print_with_color(symbol, Color::Black, color)?;
+                frame.put(
+                    screen_x,
+                    screen_y,
+                    &format!(" {} ", symbol),
+                    background,
+                    color,
+                );
+                if let Some(mark) = mark.filter(|_| glyphs) {
+                    let (open, close) = mark.brackets();
+                    frame.put(
+                        screen_x,
+                        screen_y,
+                        &open.to_string(),
+                        background,
+                        theme.text,
+                    );
+                    frame.put(
+                        screen_x + 2,
+                        screen_y,
+                        &close.to_string(),
+                        background,
+                        theme.text,
+                    );
+                }
 
                 if self.cursor_loc == TerminalPos(x as u16, y as u16) {
-                    move_cursor(screen_x, screen_y)?;
-                    print_with_color("[", Color::Blue, Color::White)?;
-                    move_cursor(screen_x + 2, screen_y)?;
-                    print_with_color("]", Color::Blue, Color::White)?;
+                    frame.put(
+                        screen_x,
+                        screen_y,
+                        "[",
+                        theme.selection,
+                        theme.selection_text,
+                    );
+                    frame.put(
+                        screen_x + 2,
+                        screen_y,
+                        "]",
+                        theme.selection,
+                        theme.selection_text,
+                    );
                 }
             }
         }
-
-        Ok(())
     }
 
     pub fn reset_grid(&mut self) {
@@ -287,51 +669,139 @@ impl Game {
         }
     }
 
-    pub fn generate_random_map(&mut self) {
-        let mut rng = rand::rng();
-        self.reset_grid();
-
-        let player_position = random_coord(&mut rng);
-        let enemy_position = loop {
-            let p = random_coord(&mut rng);
-            if p != player_position {
+    fn place_city<R: Rng>(&mut self, rng: &mut R, city: City) -> TerminalPos {
+        loop {
+            let p = random_coord(rng);
+            if self.hill() == Some(p) {
+                continue;
+            }
+            let cell = &mut self.grid[p.1 as usize][p.0 as usize];
+            if cell.city.is_none() {
+                cell.city = Some(city);
                 break p;
             }
-        };
+        }
+    }
 
-        self.grid[player_position.1 as usize][player_position.0 as usize].city = Some(City {
-            state: CityState::OwnedByPlayer,
-            generation_level: self.difficulty.starting_player_level(),
-            combat_level: self.difficulty.starting_player_level(),
-            resources: self.difficulty.starting_resources(),
-        });
+    pub fn generate_random_map(&mut self) {
+        let mut rng = rand::rng();
+        let block_chance = self.difficulty.block_chance();
+        let hill = self.hill();
+
+        // Reroll everything until all cities can actually reach each other.
+        let player_position = loop {
+            self.reset_grid();
+
+            let player_position = self.place_city(
+                &mut rng,
+                City {
+                    state: CityState::OwnedByPlayer,
+                    generation_level: self.difficulty.starting_player_level(),
+                    combat_level: self.difficulty.starting_player_level(),
+                    resources: self.difficulty.starting_resources(),
+                    ..City::default()
+                },
+            );
+
+            self.place_city(
+                &mut rng,
+                City {
+                    state: CityState::OwnedByComputer,
+                    generation_level: self.difficulty.starting_enemy_level(),
+                    combat_level: self.difficulty.starting_enemy_level(),
+                    resources: self.difficulty.starting_resources(),
+                    ..City::default()
+                },
+            );
+
+            for _ in 0..self.difficulty.neutral_city_count() {
+                self.place_city(
+                    &mut rng,
+                    City {
+                        state: CityState::Neutral,
+                        combat_level: rules().neutral_combat_level,
+                        ..City::default()
+                    },
+                );
+            }
 
-        self.grid[enemy_position.1 as usize][enemy_position.0 as usize].city = Some(City {
-            state: CityState::OwnedByComputer,
-            generation_level: self.difficulty.starting_enemy_level(),
-            combat_level: self.difficulty.starting_enemy_level(),
-            resources: self.difficulty.starting_resources(),
-        });
+            if self.barbarians {
+                for _ in 0..self.difficulty.barbarian_camp_count() {
+                    self.place_city(
+                        &mut rng,
+                        City {
+                            state: CityState::Barbarian,
+                            combat_level: rules().barbarian_combat_level,
+                            ..City::default()
+                        },
+                    );
+                }
+            }
 
-        let block_chance = self.difficulty.block_chance();
+            for y in 0..GRID_SIZE {
+                for x in 0..GRID_SIZE {
+                    let cell = &mut self.grid[y][x];
 
-        for y in 0..GRID_SIZE {
-            for x in 0..GRID_SIZE {
-                let cell = &mut self.grid[y][x];
+                    if cell.city.is_some() {
+                        continue;
+                    }
 
-                if cell.city.is_some() {
-                    continue;
+                    cell.terrain = if hill == Some(TerminalPos(x as u16, y as u16)) {
+                        Terrain::Plains
+                    } else if rng.random_bool(block_chance) {
+                        Terrain::Wall
+                    } else {
+                        random_terrain(&mut rng)
+                    };
                 }
+            }
 
-                if rng.random_bool(block_chance) {
-                    cell.blocked = true;
-                }
+            if self.is_map_connected() && self.is_hill_reachable() {
+                break player_position;
             }
-        }
+        };
 
         self.cursor_loc = player_position;
     }
 
+    pub fn prompt_barbarians(&mut self) {
+        let menu = inquire::Confirm::new("Add barbarian camps to the map?").with_default(false);
+        self.barbarians = menu.prompt().unwrap_or(false);
+    }
+
+    pub fn prompt_turn_limit(&mut self) {
+        let mut choices = vec!["No limit".to_string()];
+        choices.extend(
+            TURN_LIMIT_CHOICES
+                .iter()
+                .map(|turns| format!("{} turns", turns)),
+        );
+
+        let menu = inquire::Select::new("Choose a turn limit.", choices)
+            .with_help_message("When time runs out, the higher score wins.");
+        self.turn_limit = match menu.raw_prompt() {
+            Ok(choice) if choice.index > 0 => Some(TURN_LIMIT_CHOICES[choice.index - 1]),
+            _ => None,
+        };
+    }
+
+    pub fn prompt_events(&mut self) {
+        let menu = inquire::Confirm::new("Turn on random world events?")
+            .with_default(false)
+            .with_help_message("Plagues, harvests, earthquakes and rebellions between turns.");
+        self.events = menu.prompt().unwrap_or(false);
+        if self.events {
+            // Entering the seed from an earlier game's log replays its events.
+            let seed = rand::rng().random();
+            self.event_seed = inquire::CustomType::<u64>::new("Event seed?")
+                .with_default(seed)
+                .with_help_message("Keep this one, or enter the seed of a game to replay.")
+                .prompt()
+                .unwrap_or(seed);
+            self.log_event(format!("World events are on (seed {}).", self.event_seed));
+        }
+    }
+
     pub fn prompt_difficulty(&mut self) {
         let menu = inquire::Select::new(
             "Choose a difficulty.",
@@ -347,12 +817,24 @@ impl Game {
     }
 
     pub fn draw(&self) -> io::Result<()> {
-        clear_screen()?;
-        self.draw_grid()?;
-        self.draw_grid_text()?;
-        self.draw_instructions()?;
-        self.draw_cell_info()?;
-        Ok(())
+        present(self.frame()?)
+    }
+
+    // The game screen, for anything that wants to draw on top of it first.
+    pub fn frame(&self) -> io::Result<Frame> {
+        let mut frame = Frame::for_terminal()?;
+        let Some(layout) = Layout::for_size(frame.width, frame.height) else {
+            draw_too_small(&mut frame);
+            return Ok(frame);
+        };
+        self.draw_grid(&mut frame, layout);
+        self.draw_grid_text(&mut frame, layout);
+        self.draw_instructions(&mut frame, layout);
+        self.draw_cell_info(&mut frame, layout);
+        self.draw_panel(&mut frame, layout);
+        self.draw_status(&mut frame, layout);
+        self.draw_log(&mut frame, layout);
+        Ok(frame)
     }
 
     pub fn is_valid_grid_position(&self, pos: TerminalPos) -> Result<(), GameError> {
@@ -391,80 +873,398 @@ impl Game {
             .ok_or(GameError::NoCityAtTarget)
     }
 
+    pub fn get_source_city_at_pos(&self, pos: TerminalPos) -> Result<&City, GameError> {
+        match self.get_city_at_pos(pos) {
+            Ok(city) if city.state != CityState::Destroyed => Ok(city),
+            Ok(_) | Err(GameError::NoCityAtTarget) => Err(GameError::NoCityAtSource),
+            Err(game_error) => Err(game_error),
+        }
+    }
+
+    pub fn get_mut_source_city_at_pos(&mut self, pos: TerminalPos) -> Result<&mut City, GameError> {
+        match self.get_mut_city_at_pos(pos) {
+            Ok(city) if city.state != CityState::Destroyed => Ok(city),
+            Ok(_) | Err(GameError::NoCityAtTarget) => Err(GameError::NoCityAtSource),
+            Err(game_error) => Err(game_error),
+        }
+    }
+
+    // Positions of every living city, optionally only those of one owner.
+    pub fn city_positions(&self, owner: Option<CityState>) -> Vec<TerminalPos> {
+        let mut positions = Vec::new();
+        for (y, row) in self.grid.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let Some(city) = cell.city else {
+                    continue;
+                };
+                if city.state == CityState::Destroyed {
+                    continue;
+                }
+                if owner.is_some_and(|owner| owner != city.state) {
+                    continue;
+                }
+                positions.push(TerminalPos(x as u16, y as u16));
+            }
+        }
+        positions
+    }
+
+    pub fn begin_turn(&mut self, owner: CityState) {
+        self.advance_convoys(owner);
+        self.advance_research(owner);
+
+        for row in self.grid.iter_mut() {
+            for cell in row.iter_mut() {
+                if let Some(city) = cell.city.as_mut()
+                    && city.state == owner
+                {
+                    city.has_acted = false;
+                }
+            }
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        matches!(
+            self.state,
+            GameState::PlayerWon
+                | GameState::ComputerWon
+                | GameState::Stalemate
+                | GameState::Abandoned
+        )
+    }
+
+    pub fn update_victory_state(&mut self) {
+        let player_alive = !self
+            .city_positions(Some(CityState::OwnedByPlayer))
+            .is_empty();
+        let computer_alive = !self
+            .city_positions(Some(CityState::OwnedByComputer))
+            .is_empty();
+
+        self.state = match (player_alive, computer_alive) {
+            (true, true) => return,
+            (true, false) => GameState::PlayerWon,
+            (false, true) => GameState::ComputerWon,
+            (false, false) => GameState::Stalemate,
+        };
+        if self.state != GameState::Stalemate {
+            self.victory = Some(VictoryCondition::Conquest);
+        }
+    }
+
+    pub fn log_event(&mut self, text: String) {
+        self.log.push(format!("Turn {}: {}", self.turn, text));
+    }
+
     pub fn do_action(&mut self, action: GameAction) -> Result<(), GameError> {
+        let source = action.source();
+        if self.get_source_city_at_pos(source)?.has_acted {
+            return Err(GameError::CityAlreadyActed);
+        }
+
         match action {
             GameAction::Produce { source } => {
-                let source_city = match self.get_mut_city_at_pos(source) {
-                    Ok(city) => city,
-                    Err(game_error) => {
-                        if game_error == GameError::NoCityAtTarget {
-                            return Err(GameError::NoCityAtSource);
-                        }
-                        return Err(game_error);
-                    }
-                };
-                source_city.produce();
-                Ok(())
+                let owner = self.get_source_city_at_pos(source)?.state;
+                let bonus = self.get_cell_at_pos(source)?.terrain.production_bonus()
+                    + self.production_bonus(owner);
+                self.get_mut_source_city_at_pos(source)?.produce(bonus);
             }
             GameAction::UpgradeAttack { source } => {
-                let source_city = match self.get_mut_city_at_pos(source) {
-                    Ok(city) => city,
-                    Err(game_error) => {
-                        if game_error == GameError::NoCityAtTarget {
-                            return Err(GameError::NoCityAtSource);
-                        }
-                        return Err(game_error);
-                    }
-                };
-                source_city.upgrade_attack()?;
-                Ok(())
+                self.get_mut_source_city_at_pos(source)?.upgrade_attack()?;
             }
             GameAction::UpgradeProduce { source } => {
-                let source_city = match self.get_mut_city_at_pos(source) {
-                    Ok(city) => city,
-                    Err(game_error) => {
-                        if game_error == GameError::NoCityAtTarget {
-                            return Err(GameError::NoCityAtSource);
-                        }
-                        return Err(game_error);
-                    }
+                self.get_mut_source_city_at_pos(source)?
+                    .upgrade_production()?;
+            }
+            GameAction::DestroyWall { source, target } => {
+                let cell = *self.get_cell_at_pos(target)?;
+                let is_ruins = cell
+                    .city
+                    .is_some_and(|city| city.state == CityState::Destroyed);
+                if !cell.is_wall() && !is_ruins {
+                    return Err(GameError::NoWallAtTarget);
+                }
+                let owner = self.get_source_city_at_pos(source)?.state;
+                self.require_path_within(source, target, self.build_range(owner))?;
+                let cost = self.demolish_cost(owner, is_ruins);
+                self.get_mut_source_city_at_pos(source)?.spend(cost)?;
+
+                let cell = self.get_mut_cell_at_pos(target)?;
+                if is_ruins {
+                    cell.city = None;
+                    self.log_event(format!("{} cleared the ruins at {}.", owner, target));
+                } else {
+                    cell.terrain = Terrain::Plains;
+                    self.log_event(format!("{} tore down the wall at {}.", owner, target));
+                }
+            }
+            GameAction::GenerateCity { source, target } => {
+                if rules().city_founding == 0 {
+                    return Err(GameError::ActionDisabled);
+                }
+                let cell = self.get_cell_at_pos(target)?;
+                if !cell.terrain.is_buildable() || cell.city.is_some() {
+                    return Err(GameError::TargetIsOccupied);
+                }
+                let owner = self.get_source_city_at_pos(source)?.state;
+                self.require_path_within(source, target, self.build_range(owner))?;
+                let source_city = self.get_mut_source_city_at_pos(source)?;
+                source_city.spend(rules().city_found_cost)?;
+
+                // A new city starts with nothing to spend, so it may as well act right away.
+                self.get_mut_cell_at_pos(target)?.city = Some(City {
+                    state: owner,
+                    ..City::default()
+                });
+                self.log_event(format!("{} founded a city at {}.", owner, target));
+            }
+            GameAction::AttackCity {
+                source,
+                target,
+                intent,
+            } => {
+                let attacker = *self.get_source_city_at_pos(source)?;
+                let defender = *self.get_city_at_pos(target)?;
+                if defender.state == CityState::Destroyed || defender.state == attacker.state {
+                    return Err(GameError::NoCityAtTarget);
+                }
+                if intent == AttackIntent::Capture && defender.state == CityState::Barbarian {
+                    return Err(GameError::CannotCapture);
+                }
+                self.require_path_within(source, target, rules().attack_range)?;
+                self.resolve_attack(source, target, intent)?;
+            }
+            GameAction::TransferResources { source, target } => {
+                self.transfer_resources(source, target)?;
+            }
+            GameAction::SendConvoy { source, target } => {
+                self.send_convoy(source, target)?;
+            }
+            GameAction::Build { source, building } => {
+                if !self.can_build(building) {
+                    return Err(GameError::BuildingUnavailable);
+                }
+                let city = self.get_mut_source_city_at_pos(source)?;
+                city.build(building)?;
+                let owner = city.state;
+                self.log_event(format!("{} built {} at {}.", owner, building, source));
+            }
+        }
+
+        self.get_mut_source_city_at_pos(source)?.has_acted = true;
+        self.last_action = Some(action);
+        self.update_victory_state();
+        Ok(())
+    }
+
+    fn resolve_attack(
+        &mut self,
+        source: TerminalPos,
+        target: TerminalPos,
+        intent: AttackIntent,
+    ) -> Result<(), GameError> {
+        let attacker = *self.get_city_at_pos(source)?;
+        let defender = *self.get_city_at_pos(target)?;
+        let attack = attacker.roll_for_attack(self.attack_bonus(attacker.state));
+        let defense = defender.roll_for_attack(self.total_defense_bonus(target)?);
+        self.animations.push(Animation::Attack {
+            source,
+            target,
+            attacker: attacker.state,
+            defender: defender.state,
+            attack,
+            defense,
+        });
+        let (attack, defense) = (attack.value, defense.value);
+        let by_player = attacker.state == CityState::OwnedByPlayer;
+        let on_player = defender.state == CityState::OwnedByPlayer;
+        let capturing = intent == AttackIntent::Capture && attacker.state != CityState::Barbarian;
+
+        if attack <= defense {
+            // A failed attack costs the attacker some of its readiness.
+            let attacker_city = self.get_mut_city_at_pos(source)?;
+            attacker_city.combat_level = attacker_city
+                .combat_level
+                .saturating_sub(rules().failed_attack_penalty)
+                .max(1);
+            self.status = Some(match (by_player, on_player, attacker.state) {
+                (true, _, _) => "Your attack was repelled.",
+                (_, true, CityState::Barbarian) => "You fought off a barbarian raid.",
+                (_, true, _) => "You repelled an enemy attack.",
+                _ => "An attack was repelled.",
+            });
+            self.log_event(format!(
+                "{} city at {} repelled an attack from {} ({} vs {}).",
+                defender.state, target, attacker.state, defense, attack
+            ));
+            return Ok(());
+        }
+
+        // Winning, but not by enough to take the city over.
+        if capturing && attack <= defense + self.capture_margin(attacker.state) {
+            self.status = Some(match (by_player, on_player) {
+                (true, _) => "The city held out against your capture attempt.",
+                (_, true) => "One of your cities held out against a capture attempt.",
+                _ => "A city held out against a capture attempt.",
+            });
+            self.log_event(format!(
+                "{} city at {} held out against capture by {} ({} vs {}).",
+                defender.state, target, attacker.state, defense, attack
+            ));
+            return Ok(());
+        }
+
+        match (attacker.state, defender.state) {
+            // Barbarians don't hold territory, they just take what they can carry.
+            (CityState::Barbarian, _) => {
+                self.get_mut_city_at_pos(target)?.resources = 0;
+                self.get_mut_city_at_pos(source)?.resources += defender.resources;
+                self.status = Some(if on_player {
+                    "Barbarians raided one of your cities!"
+                } else {
+                    "Barbarians raided a city."
+                });
+                self.log_event(format!(
+                    "Barbarians raided the {} city at {}.",
+                    defender.state, target
+                ));
+            }
+            (_, CityState::Barbarian) => {
+                self.count_destroyed(attacker.state);
+                self.animations.push(Animation::Ruins(target));
+                let camp = self.get_mut_city_at_pos(target)?;
+                camp.state = CityState::Destroyed;
+                camp.resources = 0;
+                camp.buildings = Default::default();
+                self.get_mut_city_at_pos(source)?.resources +=
+                    defender.resources + rules().barbarian_camp_bounty;
+                self.status = Some(if by_player {
+                    "You wiped out a barbarian camp and took its loot!"
+                } else {
+                    "The enemy wiped out a barbarian camp."
+                });
+                self.log_event(format!(
+                    "{} wiped out the barbarian camp at {}.",
+                    attacker.state, target
+                ));
+            }
+            _ if capturing => {
+                let captured = self.get_mut_city_at_pos(target)?;
+                let plunder = captured.resources;
+                captured.state = attacker.state;
+                let divisor = rules().capture_level_divisor;
+                captured.generation_level = (captured.generation_level / divisor).max(1);
+                captured.combat_level = (captured.combat_level / divisor).max(1);
+                captured.resources = 0;
+                captured.has_acted = true;
+
+                let bonus = if defender.state == CityState::Neutral {
+                    rules().neutral_capture_bonus
+                } else {
+                    0
                 };
-                source_city.upgrade_production()?;
-                Ok(())
+                self.get_mut_city_at_pos(source)?.resources += plunder + bonus;
+                self.status = Some(match (by_player, on_player) {
+                    (true, _) => "You captured the city!",
+                    (_, true) => "The enemy captured one of your cities!",
+                    _ => "The enemy captured a city.",
+                });
+                self.log_event(format!(
+                    "{} captured the {} city at {} and plundered {} Resources.",
+                    attacker.state,
+                    defender.state,
+                    target,
+                    plunder + bonus
+                ));
+            }
+            _ => {
+                self.count_destroyed(attacker.state);
+                self.animations.push(Animation::Ruins(target));
+                let defender_city = self.get_mut_city_at_pos(target)?;
+                defender_city.state = CityState::Destroyed;
+                defender_city.resources = 0;
+                defender_city.buildings = Default::default();
+                self.status = Some(match (by_player, on_player) {
+                    (true, _) => "Your attack succeeded! The city lies in ruins.",
+                    (_, true) => "The enemy destroyed one of your cities!",
+                    _ => "The enemy destroyed a city.",
+                });
+                self.log_event(format!(
+                    "{} razed the {} city at {}.",
+                    attacker.state, defender.state, target
+                ));
             }
-            _ => Ok(()),
         }
+
+        Ok(())
     }
 }
 
 impl City {
-    pub fn roll_for_attack(&self) -> u32 {
+    pub fn roll_range(&self) -> (u32, u32) {
+        let rules = rules();
+        let min_roll: u32 = self.combat_level.saturating_sub(rules.roll_min_offset) + 1;
+        let max_roll: u32 = self.combat_level.saturating_add(rules.roll_max_offset);
+        (min_roll, max_roll.max(min_roll))
+    }
+
+    // `bonus` is added on top of the roll, e.g. for defending on good terrain.
+    pub fn roll_for_attack(&self, bonus: u32) -> Roll {
         let mut rng = rand::rng();
 
-        let min_roll: u32 = self.combat_level.saturating_sub(5) + 1;
-        let max_roll: u32 = self.combat_level.saturating_add(1);
+        let (min_roll, max_roll) = self.roll_range();
 
-        rng.random_range(min_roll..=max_roll)
+        Roll {
+            value: rng.random_range(min_roll..=max_roll) + bonus,
+            min: min_roll + bonus,
+            max: max_roll + bonus,
+        }
     }
 
-    pub fn produce(&mut self) {
-        self.resources += self.generation_level.div_ceil(2);
+    pub fn produce(&mut self, bonus: u32) {
+        self.resources += self
+            .generation_level
+            .div_ceil(rules().production_level_divisor)
+            + bonus
+            + self.building_production_bonus();
     }
 
     pub fn get_power(&self) -> u32 {
-        let resources_bonus = self.resources.div_ceil(2);
+        let rules = rules();
+        let resources_bonus = self.resources.div_ceil(rules.power_resource_divisor);
         self.combat_level
             .saturating_add(self.generation_level)
             .saturating_add(resources_bonus)
-            .div_ceil(3)
+            .div_ceil(rules.power_divisor)
     }
 
     pub fn get_resource_amount_to_upgrade_attack(&self) -> u32 {
-        self.combat_level * 5
+        if self.has_building(Building::Barracks) {
+            return self
+                .combat_level
+                .saturating_mul(rules().barracks_cost_per_level);
+        }
+        self.combat_level
+            .saturating_mul(rules().attack_upgrade_cost_per_level)
     }
 
     pub fn get_resource_amount_to_upgrade_produce(&self) -> u32 {
-        self.generation_level * 5
+        if self.has_building(Building::Granary) {
+            return self
+                .generation_level
+                .saturating_mul(rules().granary_cost_per_level);
+        }
+        self.generation_level
+            .saturating_mul(rules().produce_upgrade_cost_per_level)
+    }
+
+    pub fn spend(&mut self, amount: u32) -> Result<(), GameError> {
+        if self.resources < amount {
+            return Err(GameError::NotEnoughResources);
+        }
+        self.resources -= amount;
+        Ok(())
     }
 
     pub fn upgrade_attack(&mut self) -> Result<(), GameError> {
@@ -495,6 +1295,8 @@ impl Default for City {
             generation_level: 1,
             combat_level: 1,
             resources: 0,
+            has_acted: false,
+            buildings: Default::default(),
         }
     }
 }
@@ -0,0 +1,303 @@
+use crossterm::event::KeyCode;
+use std::sync::OnceLock;
+
+use crate::enums::RawCommand;
+use crate::rules::{ConfigError, key_values, read_optional_file};
+use crate::settings::settings;
+use crate::structs::Keymap;
+
+pub const DEFAULT_KEYMAP: &str = include_str!("default_keys.cfg");
+pub const KEYMAP_FILE: &str = "keys.cfg";
+
+static KEYMAP: OnceLock<Keymap> = OnceLock::new();
+
+impl RawCommand {
+    pub const ALL: [RawCommand; 10] = [
+        RawCommand::MoveUp,
+        RawCommand::MoveDown,
+        RawCommand::MoveLeft,
+        RawCommand::MoveRight,
+        RawCommand::Interact,
+        RawCommand::EndTurn,
+        RawCommand::OpenTechTree,
+        RawCommand::NextCity,
+        RawCommand::CycleOverlay,
+        RawCommand::QuitGame,
+    ];
+
+    fn config_name(self) -> &'static str {
+        match self {
+            RawCommand::MoveUp => "move_up",
+            RawCommand::MoveDown => "move_down",
+            RawCommand::MoveLeft => "move_left",
+            RawCommand::MoveRight => "move_right",
+            RawCommand::Interact => "interact",
+            RawCommand::EndTurn => "end_turn",
+            RawCommand::OpenTechTree => "tech_tree",
+            RawCommand::NextCity => "next_city",
+            RawCommand::CycleOverlay => "overlay",
+            RawCommand::QuitGame => "quit",
+            RawCommand::None => "none",
+        }
+    }
+}
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let key = match name {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return None,
+            }
+        }
+    };
+    Some(key)
+}
+
+// How a key is written on screen.
+fn display_key(key: KeyCode) -> String {
+    let unicode = settings().unicode;
+    match key {
+        KeyCode::Up if unicode => "↑".into(),
+        KeyCode::Down if unicode => "↓".into(),
+        KeyCode::Left if unicode => "←".into(),
+        KeyCode::Right if unicode => "→".into(),
+        KeyCode::Up => "up".into(),
+        KeyCode::Down => "down".into(),
+        KeyCode::Left => "left".into(),
+        KeyCode::Right => "right".into(),
+        KeyCode::Enter => "enter".into(),
+        KeyCode::Esc => "ESC".into(),
+        KeyCode::Char(' ') => "space".into(),
+        KeyCode::Tab => "tab".into(),
+        KeyCode::Backspace => "backspace".into(),
+        KeyCode::Char(c) => c.to_string(),
+        _ => "?".into(),
+    }
+}
+
+impl Keymap {
+    fn keys_mut(&mut self, command: RawCommand) -> &mut Vec<KeyCode> {
+        let index = match self.bindings.iter().position(|(c, _)| *c == command) {
+            Some(index) => index,
+            None => {
+                self.bindings.push((command, Vec::new()));
+                self.bindings.len() - 1
+            }
+        };
+        &mut self.bindings[index].1
+    }
+
+    fn keys(&self, command: RawCommand) -> &[KeyCode] {
+        self.bindings
+            .iter()
+            .find(|(c, _)| *c == command)
+            .map_or(&[], |(_, keys)| keys)
+    }
+
+    // Applies every `command = key, key` line of `text`, replacing that command's keys.
+    pub fn apply(&mut self, text: &str, source: &str) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        for (line, name, keys) in key_values(text, source, true, &mut errors) {
+            let error = |message: String| ConfigError {
+                source: source.into(),
+                line: Some(line),
+                message,
+            };
+
+            let Some(command) = RawCommand::ALL
+                .into_iter()
+                .find(|command| command.config_name() == name)
+            else {
+                errors.push(error(format!("unknown command `{}`", name)));
+                continue;
+            };
+
+            let mut parsed = Vec::new();
+            for key in keys.split(',').map(str::trim).filter(|key| !key.is_empty()) {
+                match parse_key(key) {
+                    Some(key) => parsed.push(key),
+                    None => errors.push(error(format!("unknown key `{}`", key))),
+                }
+            }
+            *self.keys_mut(command) = parsed;
+        }
+
+        errors.sort_by_key(|error| error.line);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Every command needs a key, and no key can mean two things.
+    pub fn validate(&self, source: &str) -> Result<(), Vec<ConfigError>> {
+        let error = |message: String| ConfigError {
+            source: source.into(),
+            line: None,
+            message,
+        };
+        let mut errors = Vec::new();
+
+        for (i, command) in RawCommand::ALL.into_iter().enumerate() {
+            if self.keys(command).is_empty() {
+                errors.push(error(format!(
+                    "`{}` needs at least one key",
+                    command.config_name()
+                )));
+            }
+            for other in RawCommand::ALL.into_iter().skip(i + 1) {
+                if let Some(key) = self
+                    .keys(command)
+                    .iter()
+                    .find(|key| self.keys(other).contains(key))
+                {
+                    errors.push(error(format!(
+                        "`{}` is bound to both `{}` and `{}`",
+                        display_key(*key),
+                        command.config_name(),
+                        other.config_name()
+                    )));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn defaults() -> Keymap {
+        let mut keymap = Keymap::default();
+        keymap
+            .apply(DEFAULT_KEYMAP, "built-in key bindings")
+            .expect("the built-in key bindings should always parse");
+        keymap
+    }
+
+    // The built-in bindings, with `keys.cfg` on top if there is one.
+    pub fn load() -> Result<Keymap, Vec<ConfigError>> {
+        let mut keymap = Keymap::defaults();
+
+        if let Some(text) = read_optional_file(KEYMAP_FILE)? {
+            keymap.apply(&text, KEYMAP_FILE)?;
+        }
+
+        keymap.validate(KEYMAP_FILE)?;
+        Ok(keymap)
+    }
+
+    pub fn command(&self, key: KeyCode) -> RawCommand {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map_or(RawCommand::None, |(command, _)| *command)
+    }
+
+    // The first key bound to `command`, as shown on screen.
+    pub fn key_name(&self, command: RawCommand) -> String {
+        self.keys(command)
+            .first()
+            .map_or("?".into(), |key| display_key(*key))
+    }
+
+    // Several commands' keys together, e.g. "↑↓←→", or "w/s" if any is a word.
+    pub fn key_names(&self, commands: &[RawCommand]) -> String {
+        // Spelled out, all four arrows would crowd the instructions off the screen.
+        let arrows = [KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right];
+        let primary: Vec<KeyCode> = commands
+            .iter()
+            .filter_map(|&c| self.keys(c).first().copied())
+            .collect();
+        if !settings().unicode && primary == arrows {
+            return "arrows".into();
+        }
+
+        let names: Vec<String> = commands.iter().map(|&c| self.key_name(c)).collect();
+        if names.iter().all(|name| name.chars().count() == 1) {
+            names.concat()
+        } else {
+            names.join("/")
+        }
+    }
+
+    // Kept short enough to fit the narrowest layout.
+    pub fn instructions(&self) -> String {
+        format!(
+            "[{} move, {} interact, {} end turn, {} tech, {} overlay, {} quit]",
+            self.key_names(&[
+                RawCommand::MoveUp,
+                RawCommand::MoveDown,
+                RawCommand::MoveLeft,
+                RawCommand::MoveRight
+            ]),
+            self.key_name(RawCommand::Interact),
+            self.key_name(RawCommand::EndTurn),
+            self.key_name(RawCommand::OpenTechTree),
+            self.key_name(RawCommand::CycleOverlay),
+            self.key_name(RawCommand::QuitGame)
+        )
+    }
+}
+
+// Must be called before anything asks for `keymap()` to take effect.
+pub fn set_keymap(keymap: Keymap) {
+    let _ = KEYMAP.set(keymap);
+}
+
+pub fn keymap() -> &'static Keymap {
+    KEYMAP.get_or_init(Keymap::defaults)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_replaces_a_commands_keys() {
+        let mut keymap = Keymap::defaults();
+        let text = "# vim\nmove_up = k, up # trailing\nend_turn = e\n";
+        assert_eq!(keymap.apply(text, "test"), Ok(()));
+        assert_eq!(
+            keymap.keys(RawCommand::MoveUp),
+            [KeyCode::Char('k'), KeyCode::Up]
+        );
+        assert_eq!(keymap.command(KeyCode::Char('e')), RawCommand::EndTurn);
+        assert_eq!(keymap.command(KeyCode::Char('s')), RawCommand::None);
+        assert_eq!(keymap.command(KeyCode::Tab), RawCommand::NextCity);
+    }
+
+    #[test]
+    fn apply_rejects_unknown_commands_and_keys() {
+        let mut keymap = Keymap::defaults();
+        let errors = keymap
+            .apply("jump = space\nquit = escape\n", "test")
+            .unwrap_err();
+        let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+        assert_eq!(messages, ["unknown command `jump`", "unknown key `escape`"]);
+    }
+
+    #[test]
+    fn validate_rejects_missing_and_shared_keys() {
+        let mut keymap = Keymap::defaults();
+        assert_eq!(keymap.apply("move_up = s\nquit =\n", "test"), Ok(()));
+        let errors = keymap.validate("test").unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].message.contains("`move_up` and `end_turn`"));
+        assert!(errors[1].message.contains("`quit` needs at least one key"));
+    }
+}
@@ -1,11 +1,32 @@
+mod actions;
+mod ai;
+mod animation;
+mod buildings;
+mod combat;
 mod enums;
+mod events;
 mod implementations;
+mod keymap;
+mod menu;
+mod overlay;
+mod overview;
+mod pathfinding;
+mod render;
+mod rules;
+mod scenario;
+mod score;
+mod settings;
 mod shared;
 mod structs;
+mod supply;
+mod tech;
+mod theme;
+mod tutorial;
+mod victory;
 
 use crossterm::{
     cursor,
-    event::{self},
+    event::{self, MouseButton, MouseEvent, MouseEventKind},
     execute, terminal,
 };
 use inquire::{self, InquireError};
@@ -15,9 +36,20 @@ use std::{
     fs::OpenOptions,
     io::{self, stdout},
     panic,
+    sync::Mutex,
+    time::{Duration, Instant},
 };
 
-use crate::enums::GameActionSkeleton;
+use crate::combat::percent;
+use crate::enums::{
+    Animation, AttackIntent, ColorSupport, GameAction, GameActionSkeleton, GameMode, Layout, Tech,
+    ThemeName, VictoryCondition,
+};
+use crate::keymap::set_keymap;
+use crate::render::present;
+use crate::rules::{ConfigError, Rules, rules, set_rules};
+use crate::settings::{SETTINGS_FILE, set_settings, settings};
+use crate::theme::theme;
 use crate::{
     enums::{CityState, GameError, InputOutcome},
     structs::*,
@@ -27,21 +59,32 @@ use crate::{
     shared::*,
 };
 
+// Two clicks on the same cell within this long count as a double-click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+static LAST_CLICK: Mutex<Option<(Instant, TerminalPos)>> = Mutex::new(None);
+
 // Functions
-fn main_menu_loop() -> io::Result<()> {
+fn main_menu_loop() -> io::Result<GameMode> {
     println!("Welcome to Cell Wars!");
     loop {
-        let main_menu =
-            inquire::Select::new("Choose an option.", vec!["Play", "How To Play", "Quit"]);
+        let main_menu = inquire::Select::new(
+            "Choose an option.",
+            vec!["Play", "Campaign", "How To Play", "Settings", "Quit"],
+        );
         let result = main_menu.prompt();
         match result {
             Ok("Play") => {
-                break;
+                return Ok(GameMode::Skirmish);
+            }
+            Ok("Campaign") => {
+                return Ok(GameMode::Campaign);
             }
             Ok("How To Play") => {
-                println!("The goal of the game is to destroy your opponent's cities.");
-                println!("You both start out with one city, and must gather resources.");
-                wait_for_enter(false)?;
+                return Ok(GameMode::Tutorial);
+            }
+            Ok("Settings") => {
+                settings_menu()?;
             }
             Ok("Quit") => {
                 quit(0);
@@ -58,51 +101,257 @@ fn main_menu_loop() -> io::Result<()> {
             }
         }
     }
+}
+
+// Changes are saved straight away, so they stick for the next game too.
+fn settings_menu() -> io::Result<()> {
+    loop {
+        let mut current = settings();
+        let choices = vec![
+            format!("Color theme: {}", current.theme),
+            format!(
+                "Animations: {}",
+                if current.animations { "On" } else { "Off" }
+            ),
+            "Back".to_string(),
+        ];
+        let menu = inquire::Select::new("Change a setting.", choices);
+        match menu.raw_prompt().map(|choice| choice.index) {
+            Ok(0) => {
+                let Some(theme) = choose_theme(current.theme) else {
+                    continue;
+                };
+                current.theme = theme;
+            }
+            Ok(1) => current.animations = !current.animations,
+            _ => return Ok(()),
+        }
+
+        set_settings(current);
+        if let Err(e) = current.save() {
+            println!("Could not save {}: {}", SETTINGS_FILE, e.kind());
+        }
+    }
+}
+
+fn choose_theme(current: ThemeName) -> Option<ThemeName> {
+    let start = ThemeName::ALL
+        .iter()
+        .position(|&theme| theme == current)
+        .unwrap_or(0);
+    inquire::Select::new("Choose a color theme.", ThemeName::ALL.to_vec())
+        .with_starting_cursor(start)
+        .with_help_message("Cities and convoys also differ by letter, whatever the colors.")
+        .prompt()
+        .ok()
+}
+
+// Only the scenarios up to the first unbeaten one can be picked. None goes
+// back to the main menu.
+fn choose_scenario() -> io::Result<Option<(Campaign, usize)>> {
+    let campaign = Campaign::load().unwrap_or_else(|errors| report_errors("the campaign", errors));
+    let unlocked = (campaign.completed() + 1).min(campaign.scenarios.len());
+
+    let choices: Vec<String> = campaign.scenarios[..unlocked]
+        .iter()
+        .enumerate()
+        .map(|(i, scenario)| format!("{}. {}", i + 1, scenario.name))
+        .collect();
+    let help = format!("{} more to unlock", campaign.scenarios.len() - unlocked);
+    let prompt = format!("{}: choose a scenario.", campaign.name);
+    let menu = inquire::Select::new(&prompt, choices).with_help_message(&help);
+
+    match menu.raw_prompt() {
+        Ok(choice) => Ok(Some((campaign, choice.index))),
+        Err(_) => Ok(None),
+    }
+}
+
+fn briefing_screen(scenario: &Scenario) -> io::Result<()> {
+    terminal::disable_raw_mode()?;
+    clear_screen()?;
+    print_with_color(&scenario.name, theme().background, theme().accent)?;
+    println!("\n");
+    for line in &scenario.briefing {
+        println!("{}", line);
+    }
+
+    println!("\nObjectives:");
+    println!("  - {}", VictoryCondition::Conquest.goal());
+    for condition in &scenario.victory_conditions {
+        println!("  - {}", condition.goal());
+    }
+    if let Some(limit) = scenario.turn_limit {
+        println!(
+            "  - Time limit: {} turns. If nobody has won by then, the higher score wins.",
+            limit
+        );
+    }
+    println!();
+    wait_for_enter(false)?;
+    terminal::enable_raw_mode()?;
     Ok(())
 }
 
 fn do_input(game: &mut Game) -> io::Result<InputOutcome> {
-    if let event::Event::Key(key) = event::read()? {
-        match key_to_command(key) {
-            RawCommand::MoveUp => {
-                let cursor_pos = game.cursor_loc;
-                game.cursor_up();
-                if cursor_pos != game.cursor_loc {
-                    return Ok(InputOutcome::Redraw);
-                }
-                Ok(InputOutcome::None)
+    let key = match event::read()? {
+        event::Event::Key(key) => key,
+        // The frame is sized from the terminal, so a redraw is all a resize needs.
+        event::Event::Resize(..) => return Ok(InputOutcome::Redraw),
+        event::Event::Mouse(mouse) => return mouse_input(game, mouse),
+        _ => return Ok(InputOutcome::None),
+    };
+    match key_to_command(key) {
+        RawCommand::MoveUp => {
+            let cursor_pos = game.cursor_loc;
+            game.cursor_up();
+            if cursor_pos != game.cursor_loc {
+                return Ok(InputOutcome::Redraw);
             }
-            RawCommand::MoveDown => {
-                let cursor_pos = game.cursor_loc;
-                game.cursor_down();
-                if cursor_pos != game.cursor_loc {
-                    return Ok(InputOutcome::Redraw);
-                }
-                Ok(InputOutcome::None)
+            Ok(InputOutcome::None)
+        }
+        RawCommand::MoveDown => {
+            let cursor_pos = game.cursor_loc;
+            game.cursor_down();
+            if cursor_pos != game.cursor_loc {
+                return Ok(InputOutcome::Redraw);
             }
-            RawCommand::MoveLeft => {
-                let cursor_pos = game.cursor_loc;
-                game.cursor_left();
-                if cursor_pos != game.cursor_loc {
-                    return Ok(InputOutcome::Redraw);
-                }
-                Ok(InputOutcome::None)
+            Ok(InputOutcome::None)
+        }
+        RawCommand::MoveLeft => {
+            let cursor_pos = game.cursor_loc;
+            game.cursor_left();
+            if cursor_pos != game.cursor_loc {
+                return Ok(InputOutcome::Redraw);
+            }
+            Ok(InputOutcome::None)
+        }
+        RawCommand::MoveRight => {
+            let cursor_pos = game.cursor_loc;
+            game.cursor_right();
+            if cursor_pos != game.cursor_loc {
+                return Ok(InputOutcome::Redraw);
+            }
+            Ok(InputOutcome::None)
+        }
+        RawCommand::NextCity => {
+            let cursor_pos = game.cursor_loc;
+            game.cursor_to_next_city();
+            if cursor_pos != game.cursor_loc {
+                return Ok(InputOutcome::Redraw);
             }
-            RawCommand::MoveRight => {
-                let cursor_pos = game.cursor_loc;
-                game.cursor_right();
-                if cursor_pos != game.cursor_loc {
-                    return Ok(InputOutcome::Redraw);
+            Ok(InputOutcome::None)
+        }
+        RawCommand::CycleOverlay => {
+            game.overlay = game.overlay.next();
+            Ok(InputOutcome::Redraw)
+        }
+        RawCommand::Interact => Ok(InputOutcome::Interact),
+        RawCommand::EndTurn => Ok(InputOutcome::EndTurn),
+        RawCommand::OpenTechTree => Ok(InputOutcome::TechTree),
+        RawCommand::QuitGame => Ok(InputOutcome::QuitGame),
+        RawCommand::None => Ok(InputOutcome::None),
+    }
+}
+
+fn is_double_click(pos: TerminalPos) -> bool {
+    let mut last_click = LAST_CLICK.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    let double =
+        last_click.is_some_and(|(time, last)| last == pos && now - time <= DOUBLE_CLICK_TIME);
+    // A third click starts over instead of counting as another double-click.
+    *last_click = (!double).then_some((now, pos));
+    double
+}
+
+// Clicking moves the cursor. A double-click or right-click also opens the city's actions.
+fn mouse_input(game: &mut Game, mouse: MouseEvent) -> io::Result<InputOutcome> {
+    let MouseEventKind::Down(button) = mouse.kind else {
+        return Ok(InputOutcome::None);
+    };
+    let (width, height) = terminal::size()?;
+    let Some(pos) =
+        Layout::for_size(width, height).and_then(|layout| layout.cell_at(mouse.column, mouse.row))
+    else {
+        return Ok(InputOutcome::None);
+    };
+    game.cursor_loc = pos;
+
+    Ok(match button {
+        MouseButton::Right => InputOutcome::Interact,
+        MouseButton::Left if is_double_click(pos) => InputOutcome::Interact,
+        MouseButton::Left => InputOutcome::Select,
+        MouseButton::Middle => InputOutcome::Redraw,
+    })
+}
+
+fn select_target(game: &mut Game) -> io::Result<Option<TerminalPos>> {
+    game.status = Some("Move to a target and interact with it, or quit to cancel.");
+    game.draw()?;
+    loop {
+        match do_input(game)? {
+            InputOutcome::Redraw => game.draw()?,
+            InputOutcome::Select | InputOutcome::Interact => return Ok(Some(game.cursor_loc)),
+            InputOutcome::QuitGame => return Ok(None),
+            InputOutcome::EndTurn | InputOutcome::TechTree | InputOutcome::None => {}
+        }
+    }
+}
+
+fn tech_tree_screen(game: &mut Game) -> io::Result<()> {
+    let mut selected = 0;
+    loop {
+        present(game.tech_tree_frame(selected)?)?;
+        game.status = None;
+
+        let event::Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match key_to_command(key) {
+            RawCommand::MoveUp => selected = selected.saturating_sub(1),
+            RawCommand::MoveDown => selected = (selected + 1).min(Tech::ALL.len() - 1),
+            RawCommand::Interact => {
+                let tech = Tech::ALL[selected];
+                game.status = Some(match game.start_research(CityState::OwnedByPlayer, tech) {
+                    Ok(()) => "Research started.",
+                    Err(game_error) => game_error.reason(),
+                });
+            }
+            RawCommand::QuitGame | RawCommand::OpenTechTree => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+// Shows `menu` over the game until an entry is chosen, or None if it's cancelled.
+fn run_menu(game: &Game, menu: &mut Menu) -> io::Result<Option<usize>> {
+    loop {
+        let mut frame = game.frame()?;
+        menu.draw(&mut frame);
+        let (width, height) = (frame.width, frame.height);
+        present(frame)?;
+
+        match event::read()? {
+            event::Event::Key(key) => match key_to_command(key) {
+                RawCommand::MoveUp => menu.select_previous(),
+                RawCommand::MoveDown => menu.select_next(),
+                RawCommand::Interact if menu.chosen().is_some() => return Ok(menu.chosen()),
+                RawCommand::QuitGame => return Ok(None),
+                _ => {}
+            },
+            event::Event::Mouse(mouse) if matches!(mouse.kind, MouseEventKind::Down(_)) => {
+                if !menu.contains(width, height, mouse.column, mouse.row) {
+                    return Ok(None);
+                }
+                if let Some(index) = menu.item_at(width, height, mouse.column, mouse.row) {
+                    menu.selected = index;
+                    if menu.chosen().is_some() {
+                        return Ok(menu.chosen());
+                    }
                 }
-                Ok(InputOutcome::None)
             }
-            RawCommand::Interact => Ok(InputOutcome::Interact),
-            RawCommand::EndTurn => Ok(InputOutcome::EndTurn),
-            RawCommand::QuitGame => Ok(InputOutcome::QuitGame),
-            RawCommand::None => Ok(InputOutcome::None),
+            _ => {}
         }
-    } else {
-        Ok(InputOutcome::None)
     }
 }
 
@@ -110,7 +359,7 @@ fn player_interact(pos: TerminalPos, game: &mut Game) -> Result<(), GameError> {
     game.is_valid_grid_position(pos)?;
     let cell = game.get_cell_at_pos(pos)?;
 
-    if cell.blocked {
+    if cell.is_wall() {
         game.status = Some("That's just a wall.");
         return Err(GameError::NoCityAtTarget);
     }
@@ -120,59 +369,153 @@ fn player_interact(pos: TerminalPos, game: &mut Game) -> Result<(), GameError> {
         return Err(GameError::NoCityAtTarget);
     }
 
-    let city = game.get_city_at_pos(pos)?;
-
-    if city.state == CityState::OwnedByComputer {
-        game.status = Some("You cannot act on an opposing city!");
-        return Err(GameError::NoCityAtTarget);
-    }
+    let city = *game.get_city_at_pos(pos)?;
 
     if city.state == CityState::Destroyed {
         game.status = Some("That city is destroyed...");
         return Err(GameError::NoCityAtTarget);
     }
 
-    let action_menu = inquire::Select::new(
-        "Choose an action...",
-        vec![
-            GameActionSkeleton::Produce,
-            GameActionSkeleton::UpgradeAttack,
-            GameActionSkeleton::UpgradeProduce,
-            GameActionSkeleton::GenerateCity,
-            GameActionSkeleton::AttackCity,
-            GameActionSkeleton::DestroyWall,
-        ],
-    );
+    if city.state != CityState::OwnedByPlayer {
+        return Err(GameError::NotYourCity);
+    }
 
-    terminal::disable_raw_mode()?;
+    if city.has_acted {
+        return Err(GameError::CityAlreadyActed);
+    }
 
-    println!("\n");
-    let choice = action_menu.prompt();
+    // Buildings the city already has aren't worth a line in the menu.
+    let options: Vec<ActionOption> = game
+        .action_options(pos)?
+        .into_iter()
+        .filter(|option| option.blocked != Some(GameError::AlreadyBuilt))
+        .collect();
 
-    terminal::enable_raw_mode()?;
-    execute!(stdout(), cursor::DisableBlinking)?;
+    let fee = game.transfer_fee_per_step(city.state);
+    let items = options
+        .iter()
+        .map(|option| {
+            let detail = match (option.action, option.cost) {
+                (GameActionSkeleton::TransferResources, _) if fee > 0 => format!("{}/tile", fee),
+                (_, 0) => "Free".into(),
+                (_, cost) => format!("{} Res", cost),
+            };
+            MenuItem {
+                label: option.action.to_string(),
+                detail,
+                help: option.target.description().into(),
+                reason: option.blocked.map(GameError::reason),
+            }
+        })
+        .collect();
 
-    Ok(())
+    let mut menu = Menu::new(format!("{} Resources to spend", city.resources), items);
+    let Some(choice) = run_menu(game, &mut menu)? else {
+        return Ok(());
+    };
+    let choice = options[choice].action;
+
+    let target = if choice.needs_target() {
+        let Some(target) = select_target(game)? else {
+            game.cursor_loc = pos;
+            return Ok(());
+        };
+        game.cursor_loc = pos;
+        Some(target)
+    } else {
+        None
+    };
+
+    let action = GameAction::try_from((choice, pos, target))?;
+    if let GameAction::AttackCity {
+        source,
+        target,
+        intent,
+    } = action
+        && !confirm_attack(game, source, target, intent)?
+    {
+        return Ok(());
+    }
+    game.do_action(action)
+}
+
+// Shows the odds of an attack and asks whether to go ahead. Attacks that can't
+// happen at all are left for `do_action` to explain.
+fn confirm_attack(
+    game: &Game,
+    source: TerminalPos,
+    target: TerminalPos,
+    intent: AttackIntent,
+) -> io::Result<bool> {
+    let Ok(preview) = game.combat_preview(source, target, intent) else {
+        return Ok(true);
+    };
+    let (label, detail) = match intent {
+        AttackIntent::Capture => ("Capture", format!("{} capture", percent(preview.success))),
+        AttackIntent::Raze => ("Raze", format!("{} win", percent(preview.success))),
+    };
+    let mut help = preview.summary();
+    if preview.held_out > 0.0 {
+        help = format!("{} holds out. {}", percent(preview.held_out), help);
+    }
+    let items = vec![
+        MenuItem {
+            label: label.into(),
+            detail,
+            help,
+            reason: None,
+        },
+        MenuItem {
+            label: "Back".into(),
+            detail: String::new(),
+            help: "Leave the city alone for now.".into(),
+            reason: None,
+        },
+    ];
+    let mut menu = Menu::new(format!("Attack {}?", target), items);
+    Ok(run_menu(game, &mut menu)? == Some(0))
 }
 
 fn player_turn(game: &mut Game) -> io::Result<()> {
     game.state = GameState::PlayerTurn;
+    game.begin_turn(CityState::OwnedByPlayer);
+    game.animations.push(Animation::TurnBanner(game.turn));
+    game.play_animations()?;
+    game.draw()?;
+    game.status = None;
     loop {
         let input = do_input(game)?;
         match input {
-            InputOutcome::Redraw => {
+            InputOutcome::Redraw | InputOutcome::Select => {
+                game.advance_tutorial();
                 game.draw()?;
                 game.status = None;
             }
             InputOutcome::Interact => {
-                let _ = player_interact(game.cursor_loc, game);
+                if let Err(game_error) = player_interact(game.cursor_loc, game)
+                    && game.status.is_none()
+                {
+                    game.status = Some(game_error.reason());
+                }
+                game.play_animations()?;
+                game.advance_tutorial();
+                game.draw()?;
+                game.status = None;
+                if game.is_over() {
+                    break;
+                }
+            }
+            InputOutcome::TechTree => {
+                tech_tree_screen(game)?;
+                game.draw()?;
+                game.status = None;
             }
             InputOutcome::EndTurn => {
                 break;
             }
             InputOutcome::None => {}
             InputOutcome::QuitGame => {
-                game.state = GameState::Stalemate;
+                game.state = GameState::Abandoned;
                 break;
             }
         }
@@ -180,22 +523,62 @@ fn player_turn(game: &mut Game) -> io::Result<()> {
     Ok(())
 }
 
-// This will panic. Please implement first!
-fn computer_turn(game: &mut Game) {
+fn computer_turn(game: &mut Game) -> io::Result<()> {
     game.state = GameState::ComputerTurn;
-    todo!("todo: implement computer_turn.");
+    game.begin_turn(CityState::OwnedByComputer);
+    if let Some(tech) = game.choose_computer_research() {
+        let _ = game.start_research(CityState::OwnedByComputer, tech);
+    }
+    for source in game.city_positions(Some(CityState::OwnedByComputer)) {
+        if let Some(action) = game.choose_computer_action(source) {
+            // The AI falls back to producing if its first pick turned out illegal.
+            if game.do_action(action).is_err() {
+                let _ = game.do_action(GameAction::Produce { source });
+            }
+        }
+        game.play_animations()?;
+        if game.is_over() {
+            break;
+        }
+    }
+    Ok(())
 }
 
 fn main_game_loop(game: &mut Game) -> io::Result<()> {
-    execute!(stdout(), cursor::Hide)?;
-    game.draw()?;
+    execute!(stdout(), cursor::Hide, event::EnableMouseCapture)?;
     terminal::enable_raw_mode()?;
-    while game.state != GameState::PlayerWon
-        && game.state != GameState::ComputerWon
-        && game.state != GameState::Stalemate
-    {
+    while !game.is_over() {
+        game.turn += 1;
         player_turn(game)?;
-        computer_turn(game);
+        if game.is_over() {
+            break;
+        }
+        computer_turn(game)?;
+        if game.barbarians
+            && game.turn.is_multiple_of(rules().barbarian_raid_interval)
+            && !game.is_over()
+        {
+            game.barbarian_raids();
+            game.play_animations()?;
+        }
+        if game.events && !game.is_over() {
+            game.world_events();
+        }
+        game.run_triggers();
+        game.check_victory_conditions();
+        game.check_turn_limit();
+    }
+    Ok(())
+}
+
+// The computer never moves here, so the player can take their time.
+fn tutorial_loop(game: &mut Game) -> io::Result<()> {
+    execute!(stdout(), cursor::Hide, event::EnableMouseCapture)?;
+    terminal::enable_raw_mode()?;
+    while !game.is_over() {
+        game.turn += 1;
+        player_turn(game)?;
+        game.tutorial_turn_ended();
     }
     Ok(())
 }
@@ -263,6 +646,46 @@ fn check_if_terminal() {
     }
 }
 
+fn report_errors(what: &str, errors: Vec<ConfigError>) -> ! {
+    let _ = terminal::disable_raw_mode();
+    let _ = execute!(stdout(), terminal::LeaveAlternateScreen, cursor::Show);
+    println!("Could not load {}:", what);
+    for error in errors {
+        println!("  {}", error);
+    }
+    println!("Fix the problems above and try again.");
+    let _ = wait_for_enter(false);
+    quit(1);
+}
+
+// Broken rules should stop the game before it starts, not halfway through.
+fn load_rules(overrides: Option<(&str, &str)>) {
+    match Rules::load(overrides) {
+        Ok(rules) => set_rules(rules),
+        Err(errors) => report_errors("the rules", errors),
+    }
+}
+
+fn load_settings() {
+    match Settings::load() {
+        Ok(loaded) => {
+            // The menus already follow NO_COLOR, but not the other ways of having no color.
+            if loaded.colors == ColorSupport::None {
+                inquire::set_global_render_config(inquire::ui::RenderConfig::empty());
+            }
+            set_settings(loaded);
+        }
+        Err(errors) => report_errors("the settings", errors),
+    }
+}
+
+fn load_keymap() {
+    match Keymap::load() {
+        Ok(keymap) => set_keymap(keymap),
+        Err(errors) => report_errors("the key bindings", errors),
+    }
+}
+
 // Main
 
 fn main() -> io::Result<()> {
@@ -284,14 +707,66 @@ fn main() -> io::Result<()> {
     }));
 
     check_if_terminal();
+    load_settings();
+    load_keymap();
 
     let guard = GameGuard::new();
     let mut game = Game::default();
-    main_menu_loop()?;
-    game.prompt_difficulty();
+    let campaign = loop {
+        match main_menu_loop()? {
+            GameMode::Campaign => {
+                if let Some(choice) = choose_scenario()? {
+                    break Some(choice);
+                }
+            }
+            GameMode::Skirmish => break None,
+            GameMode::Tutorial => {
+                set_rules(Rules::defaults());
+                game.start_tutorial();
+                clear_screen()?;
+                tutorial_loop(&mut game)?;
+                drop(guard);
+                if game.state == GameState::PlayerWon {
+                    println!("Tutorial complete! You're ready for a real game.");
+                } else {
+                    println!("Come back to the tutorial any time from \"How To Play\".");
+                }
+                return Ok(());
+            }
+        }
+    };
+
+    match &campaign {
+        Some((campaign, index)) => {
+            let scenario = &campaign.scenarios[*index];
+            load_rules(Some((&scenario.rules, &scenario.source)));
+            briefing_screen(scenario)?;
+            game.start_scenario(scenario);
+        }
+        None => {
+            load_rules(None);
+            game.prompt_difficulty();
+            game.prompt_barbarians();
+            game.prompt_events();
+            game.prompt_turn_limit();
+            game.prompt_victory_conditions();
+            game.generate_random_map();
+        }
+    }
     clear_screen()?;
-    game.generate_random_map();
+
     main_game_loop(&mut game)?;
     drop(guard);
+    println!("{}", game.victory_text());
+
+    if let Some((campaign, index)) = campaign
+        && game.state == GameState::PlayerWon
+    {
+        campaign.save_completed(index + 1)?;
+        match campaign.scenarios.get(index + 1) {
+            Some(next) => println!("Scenario complete! \"{}\" is now unlocked.", next.name),
+            None => println!("You beat {}! Congratulations!", campaign.name),
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,242 @@
+use crate::enums::RawCommand;
+use crate::keymap::keymap;
+use crate::settings::settings;
+use crate::shared::*;
+use crate::structs::*;
+use crate::theme::theme;
+
+// The lines a menu is boxed in with: horizontal, vertical, then the corners
+// and joins clockwise from the top left.
+const UNICODE_BOX: [char; 8] = ['─', '│', '┌', '┐', '┤', '┘', '└', '├'];
+const ASCII_BOX: [char; 8] = ['-', '|', '+', '+', '+', '+', '+', '+'];
+
+// Where a menu goes: the top left of the grid, nudged in if the screen is too small.
+struct MenuArea {
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+}
+
+impl Menu {
+    pub fn new(title: String, items: Vec<MenuItem>) -> Self {
+        let selected = items
+            .iter()
+            .position(|item| item.reason.is_none())
+            .unwrap_or(0);
+        Self {
+            title,
+            items,
+            selected,
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.items.len().saturating_sub(1));
+    }
+
+    // The selected entry, if it can be chosen.
+    pub fn chosen(&self) -> Option<usize> {
+        self.items
+            .get(self.selected)
+            .filter(|item| item.reason.is_none())
+            .map(|_| self.selected)
+    }
+
+    // Why the selected entry can't be chosen, or else what it needs.
+    fn description(&self) -> &str {
+        match self.items.get(self.selected) {
+            Some(MenuItem {
+                reason: Some(reason),
+                ..
+            }) => reason,
+            Some(item) => &item.help,
+            None => "",
+        }
+    }
+
+    fn keys_hint(&self) -> String {
+        let keys = keymap();
+        format!(
+            "{} to choose, {} to cancel",
+            keys.key_name(RawCommand::Interact),
+            keys.key_name(RawCommand::QuitGame)
+        )
+    }
+
+    fn area(&self, screen_width: u16, screen_height: u16) -> MenuArea {
+        let label_width = self.label_width();
+        let detail_width = self.detail_width();
+        let inner = (label_width + 2 + detail_width)
+            .max(self.title.chars().count() + 2)
+            .max(self.description_width())
+            .max(self.keys_hint().chars().count());
+        // Borders and a space of padding on either side.
+        let width = (inner + 4) as u16;
+        // Borders, the items, a separator and two lines of footer.
+        let height = (self.items.len() + 5) as u16;
+
+        let x = ((GRID_X_OFFSET - 1) as u16).min(screen_width.saturating_sub(width));
+        let y = (GRID_Y_OFFSET as u16).min(screen_height.saturating_sub(height));
+        MenuArea {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn label_width(&self) -> usize {
+        self.items
+            .iter()
+            .map(|item| item.label.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn description_width(&self) -> usize {
+        self.items
+            .iter()
+            .map(|item| item.reason.unwrap_or(&item.help).chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn detail_width(&self) -> usize {
+        self.items
+            .iter()
+            .map(|item| item.detail.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn contains(&self, screen_width: u16, screen_height: u16, column: u16, row: u16) -> bool {
+        let area = self.area(screen_width, screen_height);
+        (area.x..area.x + area.width).contains(&column)
+            && (area.y..area.y + area.height).contains(&row)
+    }
+
+    // The entry drawn at a screen position, if any.
+    pub fn item_at(
+        &self,
+        screen_width: u16,
+        screen_height: u16,
+        column: u16,
+        row: u16,
+    ) -> Option<usize> {
+        let area = self.area(screen_width, screen_height);
+        if column <= area.x || column >= area.x + area.width - 1 {
+            return None;
+        }
+        let index = (row as usize).checked_sub(area.y as usize + 1)?;
+        (index < self.items.len()).then_some(index)
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let area = self.area(frame.width, frame.height);
+        let inner = area.width as usize - 2;
+        let detail_width = self.detail_width();
+        let theme = theme();
+        let [
+            horizontal,
+            vertical,
+            top_left,
+            top_right,
+            right_join,
+            bottom_right,
+            bottom_left,
+            left_join,
+        ] = if settings().unicode {
+            UNICODE_BOX
+        } else {
+            ASCII_BOX
+        };
+        let vertical = vertical.to_string();
+        let border = |frame: &mut Frame, y: u16, text: &str| {
+            frame.put(area.x, y, text, theme.background, theme.accent);
+        };
+        // A line between the side borders, padded out to the full width.
+        let line = |frame: &mut Frame, y: u16, text: &str, background, foreground| {
+            border(frame, y, &vertical);
+            frame.put(
+                area.x + 1,
+                y,
+                &format!(" {:<width$} ", text, width = inner - 2),
+                background,
+                foreground,
+            );
+            frame.put(
+                area.x + area.width - 1,
+                y,
+                &vertical,
+                theme.background,
+                theme.accent,
+            );
+        };
+
+        let title = format!(" {} ", self.title);
+        border(
+            frame,
+            area.y,
+            &format!(
+                "{}{}{}{}",
+                top_left,
+                title,
+                horizontal.to_string().repeat(inner - title.chars().count()),
+                top_right
+            ),
+        );
+
+        for (i, item) in self.items.iter().enumerate() {
+            let (background, foreground) = match (i == self.selected, item.reason) {
+                (true, _) => (theme.selection, theme.selection_text),
+                (false, None) => (theme.background, theme.text),
+                (false, Some(_)) => (theme.background, theme.faint),
+            };
+            let text = format!(
+                "{:<label_width$}{:>detail_width$}",
+                item.label,
+                item.detail,
+                label_width = inner - 2 - detail_width,
+            );
+            line(frame, area.y + 1 + i as u16, &text, background, foreground);
+        }
+
+        let separator_y = area.y + 1 + self.items.len() as u16;
+        let rule = horizontal.to_string().repeat(inner);
+        border(
+            frame,
+            separator_y,
+            &format!("{}{}{}", left_join, rule, right_join),
+        );
+
+        let blocked = self
+            .items
+            .get(self.selected)
+            .is_some_and(|item| item.reason.is_some());
+        let footer_color = if blocked { theme.warning } else { theme.muted };
+        line(
+            frame,
+            separator_y + 1,
+            self.description(),
+            theme.background,
+            footer_color,
+        );
+        line(
+            frame,
+            separator_y + 2,
+            &self.keys_hint(),
+            theme.background,
+            theme.faint,
+        );
+        border(
+            frame,
+            separator_y + 3,
+            &format!("{}{}{}", bottom_left, rule, bottom_right),
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use crossterm::style::Color;
+use std::fmt;
+
+use crate::enums::*;
+use crate::keymap::keymap;
+use crate::rules::rules;
+use crate::settings::settings;
+use crate::shared::*;
+use crate::structs::*;
+use crate::theme::theme;
+
+type MarkMap = [[Option<OverlayMark>; GRID_SIZE]; GRID_SIZE];
+
+// With 16 colors the marks land on the same colors as terrain letters, and in
+// monochrome they're all one grey, so marks are drawn as brackets instead.
+pub fn overlay_glyphs() -> bool {
+    settings().colors == ColorSupport::None
+        || settings().colors == ColorSupport::Basic
+        || settings().theme == ThemeName::Monochrome
+}
+
+impl OverlayMark {
+    pub fn tint(self) -> Color {
+        let theme = theme();
+        match self {
+            OverlayMark::AttackRange => theme.attack_range,
+            OverlayMark::BuildRange => theme.build_range,
+            OverlayMark::Threat => theme.threat,
+            OverlayMark::Reachable => theme.reachable,
+        }
+    }
+
+    // Drawn either side of the cell's letter, where the cursor's `[` `]` go.
+    pub fn brackets(self) -> (char, char) {
+        match self {
+            OverlayMark::AttackRange => ('(', ')'),
+            OverlayMark::BuildRange => ('{', '}'),
+            OverlayMark::Threat => ('!', '!'),
+            OverlayMark::Reachable => (':', ':'),
+        }
+    }
+}
+
+impl Overlay {
+    pub fn next(self) -> Overlay {
+        match self {
+            Overlay::None => Overlay::Range,
+            Overlay::Range => Overlay::Threats,
+            Overlay::Threats => Overlay::Reach,
+            Overlay::Reach => Overlay::None,
+        }
+    }
+
+    // What each mark means, for the legend.
+    fn legend(self) -> Vec<(&'static str, OverlayMark)> {
+        match self {
+            Overlay::None => Vec::new(),
+            Overlay::Range => vec![
+                ("attack", OverlayMark::AttackRange),
+                ("found", OverlayMark::BuildRange),
+            ],
+            Overlay::Threats => vec![("can attack it", OverlayMark::Threat)],
+            Overlay::Reach => vec![("reachable", OverlayMark::Reachable)],
+        }
+    }
+}
+
+impl fmt::Display for Overlay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Overlay::None => "None",
+            Overlay::Range => "Range",
+            Overlay::Threats => "Threats",
+            Overlay::Reach => "Reach",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl Game {
+    // The living city under the cursor, which every overlay is drawn around.
+    fn overlay_city(&self) -> Option<City> {
+        self.get_city_at_pos(self.cursor_loc)
+            .ok()
+            .copied()
+            .filter(|city| city.state != CityState::Destroyed)
+    }
+
+    // How the current overlay marks each cell, if at all.
+    pub fn overlay_marks(&self) -> MarkMap {
+        let mut marks: MarkMap = [[None; GRID_SIZE]; GRID_SIZE];
+        if self.overlay == Overlay::None {
+            return marks;
+        }
+        let Some(city) = self.overlay_city() else {
+            return marks;
+        };
+        let source = self.cursor_loc;
+
+        match self.overlay {
+            Overlay::None => {}
+            // Uses the same distances `do_action` checks ranges against.
+            Overlay::Range => {
+                let distances = self.distance_map(source);
+                let build_range = self.build_range(city.state);
+                for (y, row) in self.grid.iter().enumerate() {
+                    for (x, cell) in row.iter().enumerate() {
+                        let Some(distance) = distances[y][x] else {
+                            continue;
+                        };
+                        if TerminalPos(x as u16, y as u16) == source {
+                            continue;
+                        }
+                        marks[y][x] = if cell.terrain.is_buildable()
+                            && cell.city.is_none()
+                            && distance <= build_range
+                        {
+                            Some(OverlayMark::BuildRange)
+                        } else if distance <= rules().attack_range {
+                            Some(OverlayMark::AttackRange)
+                        } else {
+                            None
+                        };
+                    }
+                }
+            }
+            // Neutral cities never attack, so only the factions that do are checked.
+            Overlay::Threats => {
+                let attackers = [
+                    CityState::OwnedByPlayer,
+                    CityState::OwnedByComputer,
+                    CityState::Barbarian,
+                ];
+                for pos in self.city_positions(None) {
+                    let Ok(other) = self.get_city_at_pos(pos) else {
+                        continue;
+                    };
+                    if other.state == city.state || !attackers.contains(&other.state) {
+                        continue;
+                    }
+                    if self
+                        .require_path_within(pos, source, rules().attack_range)
+                        .is_ok()
+                    {
+                        marks[pos.1 as usize][pos.0 as usize] = Some(OverlayMark::Threat);
+                    }
+                }
+            }
+            // Walls and ruins get a distance too, but only as the end of a path.
+            Overlay::Reach => {
+                let distances = self.distance_map(source);
+                for (y, row) in distances.iter().enumerate() {
+                    for (x, distance) in row.iter().enumerate() {
+                        let pos = TerminalPos(x as u16, y as u16);
+                        let target = self.get_city_at_pos(pos).is_ok_and(|other| {
+                            other.state != CityState::Destroyed && other.state != city.state
+                        });
+                        if distance.is_some()
+                            && pos != source
+                            && (target || self.is_passable_for(pos, Some(city.state)))
+                        {
+                            marks[y][x] = Some(OverlayMark::Reachable);
+                        }
+                    }
+                }
+            }
+        }
+        marks
+    }
+
+    // One line naming the overlay and its marks. Takes no room when it's off.
+    pub fn draw_overlay_legend(&self, frame: &mut Frame, screen_y: u16) -> u16 {
+        if self.overlay == Overlay::None {
+            return screen_y;
+        }
+        let theme = theme();
+        let mut screen_x = PANEL_X as u16;
+        let title = format!(
+            "{} ({}):",
+            self.overlay,
+            keymap().key_name(RawCommand::CycleOverlay)
+        );
+        frame.put(screen_x, screen_y, &title, theme.background, theme.accent);
+        screen_x += title.chars().count() as u16 + 1;
+
+        if self.overlay_city().is_none() {
+            frame.put(
+                screen_x,
+                screen_y,
+                "move onto a city",
+                theme.background,
+                theme.faint,
+            );
+            return screen_y + 2;
+        }
+        for (label, mark) in self.overlay.legend() {
+            if overlay_glyphs() {
+                let (open, close) = mark.brackets();
+                let sample = format!("{}{}", open, close);
+                frame.put(screen_x, screen_y, &sample, theme.background, theme.text);
+            } else {
+                frame.put(screen_x, screen_y, "  ", mark.tint(), theme.text);
+            }
+            frame.put(screen_x + 3, screen_y, label, theme.background, theme.text);
+            screen_x += label.len() as u16 + 5;
+        }
+        screen_y + 2
+    }
+}
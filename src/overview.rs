@@ -0,0 +1,147 @@
+use crate::enums::*;
+use crate::keymap::keymap;
+use crate::shared::*;
+use crate::structs::*;
+use crate::theme::theme;
+
+impl Game {
+    // Moves the cursor to the player's next city after the one it's on, wrapping around.
+    pub fn cursor_to_next_city(&mut self) {
+        let cities = self.city_positions(Some(CityState::OwnedByPlayer));
+        let next = match cities.iter().position(|&pos| pos == self.cursor_loc) {
+            Some(i) => cities.get(i + 1).or(cities.first()),
+            None => cities.first(),
+        };
+        if let Some(&next) = next {
+            self.cursor_loc = next;
+        }
+    }
+
+    // Everything beside the grid, stacked from the top until it runs out of room.
+    pub fn draw_panel(&self, frame: &mut Frame, layout: Layout) {
+        let screen_y = self.draw_score(frame, GRID_Y_OFFSET as u16);
+        let screen_y = self.draw_overlay_legend(frame, screen_y);
+        let screen_y = self.draw_objectives(frame, screen_y);
+        let screen_y = self.draw_tutorial(frame, layout, screen_y);
+        self.draw_overview(frame, screen_y, layout.below_grid() as u16);
+    }
+
+    // The player's cities, what they add up to, and what's known of the enemy.
+    fn draw_overview(&self, frame: &mut Frame, top: u16, bottom: u16) {
+        // The header, at least one city, the total and the enemy line.
+        if top + 4 > bottom {
+            return;
+        }
+        let screen_x = PANEL_X as u16;
+        let theme = theme();
+        let owner = CityState::OwnedByPlayer;
+        let cities: Vec<(TerminalPos, City)> = self
+            .city_positions(Some(owner))
+            .into_iter()
+            .filter_map(|pos| Some((pos, *self.get_city_at_pos(pos).ok()?)))
+            .collect();
+
+        frame.put(
+            screen_x,
+            top,
+            &format!(
+                "{:<12}{:>5}{:>5}{:>5}{:>6}",
+                format!("Cities ({})", keymap().key_name(RawCommand::NextCity)),
+                "Prod",
+                "Cmbt",
+                "Res",
+                "Power"
+            ),
+            theme.background,
+            theme.accent,
+        );
+
+        // Leave room for the total and enemy lines, and for a "more" line if needed.
+        let room = (bottom - top - 3) as usize;
+        let shown = if cities.len() > room {
+            room - 1
+        } else {
+            cities.len()
+        };
+        let mut screen_y = top + 1;
+        for (pos, city) in &cities[..shown] {
+            let marker = if *pos == self.cursor_loc { '>' } else { ' ' };
+            // Cities that are done for the turn fade out.
+            let color = if city.has_acted {
+                theme.faint
+            } else {
+                theme.text
+            };
+            frame.put(
+                screen_x,
+                screen_y,
+                &format!(
+                    "{}{:<11}{:>5}{:>5}{:>5}{:>6}",
+                    marker,
+                    pos.to_string(),
+                    city.generation_level,
+                    city.combat_level,
+                    city.resources,
+                    city.get_power()
+                ),
+                theme.background,
+                color,
+            );
+            screen_y += 1;
+        }
+        if shown < cities.len() {
+            frame.put(
+                screen_x,
+                screen_y,
+                &format!(" ...and {} more", cities.len() - shown),
+                theme.background,
+                theme.faint,
+            );
+            screen_y += 1;
+        }
+
+        let resources = self.total_resources(owner);
+        let power: u32 = cities.iter().map(|(_, city)| city.get_power()).sum();
+        frame.put(
+            screen_x,
+            screen_y,
+            &format!(
+                "Total: {} cities, {} Res, {} power",
+                cities.len(),
+                resources,
+                power
+            ),
+            theme.background,
+            owner.color(),
+        );
+
+        frame.put(
+            screen_x,
+            screen_y + 1,
+            &self.enemy_intel(),
+            theme.background,
+            CityState::OwnedByComputer.color(),
+        );
+    }
+
+    // Enemy cities are always on the map, but their strength only shows within sight.
+    fn enemy_intel(&self) -> String {
+        let enemy = self.city_positions(Some(CityState::OwnedByComputer));
+        let seen: Vec<u32> = enemy
+            .iter()
+            .filter(|&&pos| self.is_revealed_to(pos, CityState::OwnedByPlayer))
+            .filter_map(|&pos| self.get_city_at_pos(pos).ok())
+            .map(|city| city.get_power())
+            .collect();
+
+        match seen.iter().max() {
+            Some(strongest) => format!(
+                "Enemy: {} cities, {} in sight, strongest {}",
+                enemy.len(),
+                seen.len(),
+                strongest
+            ),
+            None => format!("Enemy: {} cities, none in sight", enemy.len()),
+        }
+    }
+}
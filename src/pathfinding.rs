@@ -0,0 +1,234 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::enums::*;
+use crate::shared::*;
+use crate::structs::*;
+
+pub type DistanceMap = [[Option<u32>; GRID_SIZE]; GRID_SIZE];
+type ParentMap = [[Option<TerminalPos>; GRID_SIZE]; GRID_SIZE];
+
+pub fn neighbours(pos: TerminalPos) -> impl Iterator<Item = TerminalPos> {
+    let TerminalPos(x, y) = pos;
+    let max = (GRID_SIZE - 1) as u16;
+
+    [
+        (x > 0).then(|| TerminalPos(x - 1, y)),
+        (x < max).then(|| TerminalPos(x + 1, y)),
+        (y > 0).then(|| TerminalPos(x, y - 1)),
+        (y < max).then(|| TerminalPos(x, y + 1)),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+impl Game {
+    // Walls and ruins block everyone. Cities only let their own owner through,
+    // unless there is no owner, in which case every living city is passable.
+    pub fn is_passable_for(&self, pos: TerminalPos, owner: Option<CityState>) -> bool {
+        let Ok(cell) = self.get_cell_at_pos(pos) else {
+            return false;
+        };
+
+        if cell.is_wall() {
+            return false;
+        }
+
+        match cell.city {
+            None => true,
+            Some(city) if city.state == CityState::Destroyed => false,
+            Some(city) => owner.is_none_or(|owner| owner == city.state),
+        }
+    }
+
+    // Cost of stepping onto `pos`. Walls can only be the end of a path, so
+    // walking up to one costs a single step.
+    pub fn step_cost(&self, pos: TerminalPos) -> u32 {
+        self.get_cell_at_pos(pos)
+            .ok()
+            .and_then(|cell| cell.terrain.movement_cost())
+            .unwrap_or(1)
+    }
+
+    // Dijkstra from `from`, weighted by terrain. Impassable cells still get a
+    // distance (you can walk up to a wall or an enemy city), they just aren't
+    // expanded.
+    pub fn distance_map(&self, from: TerminalPos) -> DistanceMap {
+        let owner = self
+            .get_source_city_at_pos(from)
+            .ok()
+            .map(|city| city.state);
+        self.distance_map_for(from, owner)
+    }
+
+    pub fn distance_map_for(&self, from: TerminalPos, owner: Option<CityState>) -> DistanceMap {
+        self.search(from, owner).0
+    }
+
+    fn search(&self, from: TerminalPos, owner: Option<CityState>) -> (DistanceMap, ParentMap) {
+        let mut distances: DistanceMap = [[None; GRID_SIZE]; GRID_SIZE];
+        let mut parents: ParentMap = [[None; GRID_SIZE]; GRID_SIZE];
+
+        if self.is_valid_grid_position(from).is_err() {
+            return (distances, parents);
+        }
+
+        distances[from.1 as usize][from.0 as usize] = Some(0);
+        let mut queue = BinaryHeap::from([Reverse((0, from.0, from.1))]);
+
+        while let Some(Reverse((distance, x, y))) = queue.pop() {
+            let pos = TerminalPos(x, y);
+            if distances[y as usize][x as usize].is_some_and(|best| best < distance) {
+                continue;
+            }
+
+            for next in neighbours(pos) {
+                let next_distance = distance + self.step_cost(next);
+                let slot = &mut distances[next.1 as usize][next.0 as usize];
+                if slot.is_some_and(|best| best <= next_distance) {
+                    continue;
+                }
+                *slot = Some(next_distance);
+                parents[next.1 as usize][next.0 as usize] = Some(pos);
+
+                if self.is_passable_for(next, owner) {
+                    queue.push(Reverse((next_distance, next.0, next.1)));
+                }
+            }
+        }
+
+        (distances, parents)
+    }
+
+    // The cheapest route from `from` to `to`, not including `from` itself.
+    pub fn find_path(&self, from: TerminalPos, to: TerminalPos) -> Option<Vec<TerminalPos>> {
+        self.is_valid_grid_position(to).ok()?;
+        let owner = self
+            .get_source_city_at_pos(from)
+            .ok()
+            .map(|city| city.state);
+        let (_, parents) = self.search(from, owner);
+
+        let mut path = vec![to];
+        let mut current = to;
+        while let Some(parent) = parents[current.1 as usize][current.0 as usize] {
+            if parent == from {
+                path.reverse();
+                return Some(path);
+            }
+            path.push(parent);
+            current = parent;
+        }
+
+        None
+    }
+
+    pub fn path_distance(&self, from: TerminalPos, to: TerminalPos) -> Option<u32> {
+        self.is_valid_grid_position(to).ok()?;
+        self.distance_map(from)[to.1 as usize][to.0 as usize]
+    }
+
+    pub fn require_path_within(
+        &self,
+        from: TerminalPos,
+        to: TerminalPos,
+        range: u32,
+    ) -> Result<u32, GameError> {
+        let distance = self
+            .path_distance(from, to)
+            .ok_or(GameError::TargetNotReachable)?;
+
+        if distance > range {
+            return Err(GameError::TargetOutOfRange);
+        }
+
+        Ok(distance)
+    }
+
+    // A map is only playable if every living city can eventually reach every
+    // other one without tearing down walls. Cities in the way don't count,
+    // since they can be captured or destroyed.
+    pub fn is_map_connected(&self) -> bool {
+        let cities = self.city_positions(None);
+
+        let Some(&first) = cities.first() else {
+            return true;
+        };
+
+        let distances = self.distance_map_for(first, None);
+        cities
+            .iter()
+            .all(|pos| distances[pos.1 as usize][pos.0 as usize].is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wall(game: &mut Game, x: u16, y: u16) {
+        game.get_mut_cell_at_pos(TerminalPos(x, y)).unwrap().terrain = Terrain::Wall;
+    }
+
+    fn city(game: &mut Game, x: u16, y: u16, state: CityState) {
+        game.get_mut_cell_at_pos(TerminalPos(x, y)).unwrap().city = Some(City {
+            state,
+            ..City::default()
+        });
+    }
+
+    #[test]
+    fn paths_go_around_walls() {
+        let mut game = Game::default();
+        for y in 0..3 {
+            wall(&mut game, 1, y);
+        }
+
+        let from = TerminalPos(0, 0);
+        let to = TerminalPos(2, 0);
+        // Down the left of the wall, across under it and back up.
+        assert_eq!(game.path_distance(from, to), Some(8));
+
+        let path = game.find_path(from, to).unwrap();
+        assert_eq!(path.len(), 8);
+        assert_eq!(path.last(), Some(&to));
+        assert!(
+            path.iter()
+                .all(|&pos| !game.get_cell_at_pos(pos).unwrap().is_wall())
+        );
+    }
+
+    #[test]
+    fn walls_end_paths_but_are_never_crossed() {
+        let mut game = Game::default();
+        for y in 0..GRID_SIZE as u16 {
+            wall(&mut game, 1, y);
+        }
+
+        let from = TerminalPos(0, 0);
+        assert_eq!(game.path_distance(from, TerminalPos(1, 0)), Some(1));
+        assert_eq!(
+            game.find_path(from, TerminalPos(1, 0)),
+            Some(vec![TerminalPos(1, 0)])
+        );
+        assert_eq!(game.path_distance(from, TerminalPos(2, 0)), None);
+        assert_eq!(
+            game.require_path_within(from, TerminalPos(2, 0), u32::MAX),
+            Err(GameError::TargetNotReachable)
+        );
+    }
+
+    #[test]
+    fn a_wall_across_the_map_disconnects_it() {
+        let mut game = Game::default();
+        city(&mut game, 0, 0, CityState::OwnedByPlayer);
+        city(&mut game, 5, 5, CityState::OwnedByComputer);
+        for y in 0..GRID_SIZE as u16 {
+            wall(&mut game, 2, y);
+        }
+        assert!(!game.is_map_connected());
+
+        game.get_mut_cell_at_pos(TerminalPos(2, 9)).unwrap().terrain = Terrain::Plains;
+        assert!(game.is_map_connected());
+    }
+}
@@ -0,0 +1,217 @@
+use crossterm::{
+    cursor, queue,
+    style::{
+        Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+    },
+    terminal::{self, Clear, ClearType},
+};
+use std::{
+    io::{self, Write, stdout},
+    sync::Mutex,
+};
+
+use crate::enums::{ColorSupport, Layout, RawCommand};
+use crate::keymap::keymap;
+use crate::settings::settings;
+use crate::shared::*;
+use crate::structs::*;
+use crate::theme::{terminal_color, theme};
+
+// What the terminal is showing right now, or None if it has to be redrawn in full.
+static PREVIOUS_FRAME: Mutex<Option<Frame>> = Mutex::new(None);
+
+impl ScreenCell {
+    const BLANK: ScreenCell = ScreenCell {
+        symbol: ' ',
+        background: Color::Reset,
+        foreground: Color::Reset,
+    };
+}
+
+impl Frame {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![ScreenCell::BLANK; width as usize * height as usize],
+        }
+    }
+
+    pub fn for_terminal() -> io::Result<Self> {
+        let (width, height) = terminal::size()?;
+        Ok(Self::new(width, height))
+    }
+
+    // Writes `text` starting at (x, y). Anything past the edge is cut off.
+    pub fn put(&mut self, x: u16, y: u16, text: &str, background: Color, foreground: Color) {
+        if y >= self.height {
+            return;
+        }
+        for (i, symbol) in text.chars().enumerate() {
+            let column = x as usize + i;
+            if column >= self.width as usize {
+                break;
+            }
+            self.cells[y as usize * self.width as usize + column] = ScreenCell {
+                symbol,
+                background,
+                foreground,
+            };
+        }
+    }
+
+    // The row just below the last one with anything on it.
+    fn end_row(&self) -> u16 {
+        (0..self.height)
+            .rev()
+            .find(|&y| {
+                let start = y as usize * self.width as usize;
+                self.cells[start..start + self.width as usize]
+                    .iter()
+                    .any(|cell| *cell != ScreenCell::BLANK)
+            })
+            .map_or(0, |y| y + 1)
+    }
+}
+
+// Without colors, anything on a highlighted background is shown in reverse video instead.
+pub fn is_highlighted(background: Color) -> bool {
+    !matches!(background, Color::Black | Color::Reset)
+}
+
+// Prints only the cells that changed since the last frame, all in one flush.
+pub fn present(frame: Frame) -> io::Result<()> {
+    let mut previous = PREVIOUS_FRAME.lock().unwrap_or_else(|e| e.into_inner());
+    let mut stdout = stdout();
+
+    let old = previous
+        .as_ref()
+        .filter(|old| old.width == frame.width && old.height == frame.height);
+    if old.is_none() {
+        queue!(stdout, Clear(ClearType::All))?;
+    }
+
+    let plain = settings().colors == ColorSupport::None;
+    let mut cursor_at: Option<(u16, u16)> = None;
+    let mut colors: Option<(Color, Color)> = None;
+    for y in 0..frame.height {
+        for x in 0..frame.width {
+            let index = y as usize * frame.width as usize + x as usize;
+            let cell = frame.cells[index];
+            let unchanged = match old {
+                Some(old) => old.cells[index] == cell,
+                None => cell == ScreenCell::BLANK,
+            };
+            if unchanged {
+                continue;
+            }
+
+            if cursor_at != Some((x, y)) {
+                queue!(stdout, cursor::MoveTo(x, y))?;
+            }
+            if colors != Some((cell.background, cell.foreground)) {
+                if plain {
+                    let attribute = if is_highlighted(cell.background) {
+                        Attribute::Reverse
+                    } else {
+                        Attribute::NoReverse
+                    };
+                    queue!(stdout, SetAttribute(attribute))?;
+                } else {
+                    queue!(
+                        stdout,
+                        SetBackgroundColor(terminal_color(cell.background)),
+                        SetForegroundColor(terminal_color(cell.foreground))
+                    )?;
+                }
+                colors = Some((cell.background, cell.foreground));
+            }
+            queue!(stdout, Print(cell.symbol))?;
+            cursor_at = Some((x + 1, y));
+        }
+    }
+
+    // Prompts are printed wherever the cursor ends up, so leave it under the drawing.
+    queue!(
+        stdout,
+        ResetColor,
+        SetAttribute(Attribute::Reset),
+        cursor::MoveTo(0, frame.end_row())
+    )?;
+    stdout.flush()?;
+
+    *previous = Some(frame);
+    Ok(())
+}
+
+// Forgets the last frame, so the next one is drawn from scratch.
+pub fn invalidate_frame() {
+    *PREVIOUS_FRAME.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+impl Layout {
+    // Wide enough for the grid and the panel beside it.
+    const MIN_WIDTH: u16 = (PANEL_X + PANEL_WIDTH) as u16;
+
+    // The roomiest layout that fits, if any does.
+    pub fn for_size(width: u16, height: u16) -> Option<Layout> {
+        if width < Self::MIN_WIDTH {
+            return None;
+        }
+        [Layout::Regular, Layout::Compact]
+            .into_iter()
+            .find(|layout| layout.height() <= height)
+    }
+
+    fn row_height(self) -> usize {
+        match self {
+            Layout::Regular => 2,
+            Layout::Compact => 1,
+        }
+    }
+
+    pub fn grid_y(self, y: usize) -> u16 {
+        (GRID_Y_OFFSET + y * self.row_height()) as u16
+    }
+
+    // The first screen row under the grid, where the instructions go.
+    pub fn below_grid(self) -> usize {
+        GRID_Y_OFFSET + GRID_SIZE * self.row_height()
+    }
+
+    // The grid cell drawn at a screen position, if there is one.
+    pub fn cell_at(self, column: u16, row: u16) -> Option<TerminalPos> {
+        let x = (column as usize).checked_sub(GRID_X_OFFSET)? / 3;
+        let y = (row as usize).checked_sub(GRID_Y_OFFSET)? / self.row_height();
+        (x < GRID_SIZE && y < GRID_SIZE).then_some(TerminalPos(x as u16, y as u16))
+    }
+
+    // Instructions, cell info, status and the log all go under the grid.
+    fn height(self) -> u16 {
+        (self.below_grid() + 9 + LOG_LINES_SHOWN) as u16
+    }
+}
+
+pub fn draw_too_small(frame: &mut Frame) {
+    let theme = theme();
+    let needed = format!(
+        "Need at least {}x{}, this one is {}x{}.",
+        Layout::MIN_WIDTH,
+        Layout::Compact.height(),
+        frame.width,
+        frame.height
+    );
+    let quit = format!(
+        "Make the window bigger, or press {} to quit.",
+        keymap().key_name(RawCommand::QuitGame)
+    );
+    frame.put(
+        0,
+        0,
+        "The terminal is too small!",
+        theme.background,
+        theme.warning,
+    );
+    frame.put(0, 1, &needed, theme.background, theme.text);
+    frame.put(0, 2, &quit, theme.background, theme.text);
+}
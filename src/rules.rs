@@ -0,0 +1,572 @@
+use std::{fmt, fs, io::ErrorKind, sync::OnceLock};
+
+use crate::enums::GameDifficulty;
+use crate::shared::GRID_SIZE;
+
+pub const DEFAULT_RULES: &str = include_str!("default_rules.cfg");
+pub const RULES_FILE: &str = "rules.cfg";
+
+static RULES: OnceLock<Rules> = OnceLock::new();
+
+// Every rule is a whole number named after its field, so the struct and the
+// list of keys a ruleset may contain can't drift apart.
+macro_rules! define_rules {
+    ($($field:ident),* $(,)?) => {
+        #[derive(Clone, Debug, PartialEq, Eq, Default)]
+        pub struct Rules {
+            $(pub $field: u32,)*
+        }
+
+        impl Rules {
+            fn field_mut(&mut self, key: &str) -> Option<&mut u32> {
+                match key {
+                    $(stringify!($field) => Some(&mut self.$field),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+define_rules! {
+    easy_wall_chance_percent,
+    easy_starting_resources,
+    easy_starting_player_level,
+    easy_starting_enemy_level,
+    easy_neutral_cities,
+    easy_barbarian_camps,
+    easy_event_chance_percent,
+    easy_plague_weight,
+    easy_harvest_weight,
+    easy_earthquake_weight,
+    easy_rebellion_weight,
+    standard_wall_chance_percent,
+    standard_starting_resources,
+    standard_starting_player_level,
+    standard_starting_enemy_level,
+    standard_neutral_cities,
+    standard_barbarian_camps,
+    standard_event_chance_percent,
+    standard_plague_weight,
+    standard_harvest_weight,
+    standard_earthquake_weight,
+    standard_rebellion_weight,
+    hard_wall_chance_percent,
+    hard_starting_resources,
+    hard_starting_player_level,
+    hard_starting_enemy_level,
+    hard_neutral_cities,
+    hard_barbarian_camps,
+    hard_event_chance_percent,
+    hard_plague_weight,
+    hard_harvest_weight,
+    hard_earthquake_weight,
+    hard_rebellion_weight,
+    unfair_wall_chance_percent,
+    unfair_starting_resources,
+    unfair_starting_player_level,
+    unfair_starting_enemy_level,
+    unfair_neutral_cities,
+    unfair_barbarian_camps,
+    unfair_event_chance_percent,
+    unfair_plague_weight,
+    unfair_harvest_weight,
+    unfair_earthquake_weight,
+    unfair_rebellion_weight,
+    roll_min_offset,
+    roll_max_offset,
+    capture_margin,
+    failed_attack_penalty,
+    capture_level_divisor,
+    production_level_divisor,
+    power_resource_divisor,
+    power_divisor,
+    attack_upgrade_cost_per_level,
+    produce_upgrade_cost_per_level,
+    wall_destroy_cost,
+    ruins_clear_cost,
+    city_found_cost,
+    city_founding,
+    transfer_fee_per_step,
+    convoy_speed,
+    attack_range,
+    build_range,
+    neutral_capture_bonus,
+    neutral_combat_level,
+    barbarian_combat_level,
+    barbarian_camp_bounty,
+    barbarian_raid_interval,
+    supply_threshold,
+    ai_capture_odds_percent,
+    ai_raze_odds_percent,
+    plague_production_loss,
+    harvest_resources,
+    rebellion_max_combat_level,
+    domination_percent,
+    economic_target,
+    wonder_cost,
+    wonder_hold_turns,
+    hill_hold_turns,
+    score_per_city,
+    score_per_level,
+    score_resources_divisor,
+    score_per_city_destroyed,
+    walls_cost,
+    walls_defense,
+    barracks_cost,
+    barracks_cost_per_level,
+    market_cost,
+    market_production,
+    granary_cost,
+    granary_cost_per_level,
+    watchtower_cost,
+    watchtower_vision,
+    agriculture_cost,
+    agriculture_turns,
+    agriculture_production,
+    masonry_cost,
+    masonry_turns,
+    masonry_cost_percent,
+    fortifications_cost,
+    fortifications_turns,
+    fortifications_defense,
+    logistics_cost,
+    logistics_turns,
+    logistics_convoy_speed,
+    logistics_build_range,
+    siege_cost,
+    siege_turns,
+    siege_attack,
+    plains_movement,
+    forest_movement,
+    forest_production,
+    forest_defense,
+    mountain_movement,
+    mountain_defense,
+    river_movement,
+    river_production,
+    deposit_movement,
+    deposit_production,
+}
+
+// A problem in one of the game's text files, pointing at the line if there is one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError {
+    pub source: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.source, line, self.message),
+            None => write!(f, "{}: {}", self.source, self.message),
+        }
+    }
+}
+
+// Every `key = value` line of `text` with its line number, skipping blanks and
+// lines starting with `#`. With `inline_comments`, a `#` anywhere starts a
+// comment; otherwise it's kept, for values that are free text. Lines without
+// an `=` go straight into `errors`, so they come before anything the caller
+// finds and may need sorting back into line order.
+pub fn key_values<'a>(
+    text: &'a str,
+    source: &str,
+    inline_comments: bool,
+    errors: &mut Vec<ConfigError>,
+) -> Vec<(usize, &'a str, &'a str)> {
+    let mut pairs = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = if inline_comments {
+            line.split('#').next().unwrap_or("").trim()
+        } else {
+            line.trim()
+        };
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) => pairs.push((i + 1, key.trim(), value.trim())),
+            None => errors.push(ConfigError {
+                source: source.into(),
+                line: Some(i + 1),
+                message: format!("expected `key = value`, found `{}`", line),
+            }),
+        }
+    }
+    pairs
+}
+
+// The contents of an optional file next to the game, or None if it isn't there.
+pub fn read_optional_file(name: &str) -> Result<Option<String>, Vec<ConfigError>> {
+    match fs::read_to_string(name) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(vec![ConfigError {
+            source: name.into(),
+            line: None,
+            message: format!("could not be read ({})", e.kind()),
+        }]),
+    }
+}
+
+impl Rules {
+    // Applies every `key = value` line of `text` on top of the current values.
+    pub fn apply(&mut self, text: &str, source: &str) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        for (line, key, value) in key_values(text, source, true, &mut errors) {
+            let error = |message: String| ConfigError {
+                source: source.into(),
+                line: Some(line),
+                message,
+            };
+
+            let Some(field) = self.field_mut(key) else {
+                errors.push(error(format!("unknown rule `{}`", key)));
+                continue;
+            };
+            match value.parse() {
+                Ok(number) => *field = number,
+                Err(_) => errors.push(error(format!(
+                    "`{}` must be a whole number, found `{}`",
+                    key, value
+                ))),
+            }
+        }
+
+        errors.sort_by_key(|error| error.line);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // How likely each of `WorldEvent::ALL` is, in the same order.
+    pub fn event_weights(&self, difficulty: GameDifficulty) -> [u32; 4] {
+        match difficulty {
+            GameDifficulty::Easy => [
+                self.easy_plague_weight,
+                self.easy_harvest_weight,
+                self.easy_earthquake_weight,
+                self.easy_rebellion_weight,
+            ],
+            GameDifficulty::Standard => [
+                self.standard_plague_weight,
+                self.standard_harvest_weight,
+                self.standard_earthquake_weight,
+                self.standard_rebellion_weight,
+            ],
+            GameDifficulty::Hard => [
+                self.hard_plague_weight,
+                self.hard_harvest_weight,
+                self.hard_earthquake_weight,
+                self.hard_rebellion_weight,
+            ],
+            GameDifficulty::NotEvenRemotelyFair => [
+                self.unfair_plague_weight,
+                self.unfair_harvest_weight,
+                self.unfair_earthquake_weight,
+                self.unfair_rebellion_weight,
+            ],
+        }
+    }
+
+    // Checks the values that would break the game rather than just unbalance it.
+    pub fn validate(&self, source: &str) -> Result<(), Vec<ConfigError>> {
+        let error = |message: String| ConfigError {
+            source: source.into(),
+            line: None,
+            message,
+        };
+        let mut errors = Vec::new();
+
+        let nonzero = [
+            ("capture_level_divisor", self.capture_level_divisor),
+            ("production_level_divisor", self.production_level_divisor),
+            ("power_resource_divisor", self.power_resource_divisor),
+            ("power_divisor", self.power_divisor),
+            ("score_resources_divisor", self.score_resources_divisor),
+            ("domination_percent", self.domination_percent),
+            ("economic_target", self.economic_target),
+            ("wonder_hold_turns", self.wonder_hold_turns),
+            ("hill_hold_turns", self.hill_hold_turns),
+            ("convoy_speed", self.convoy_speed),
+            ("attack_range", self.attack_range),
+            ("build_range", self.build_range),
+            ("barbarian_raid_interval", self.barbarian_raid_interval),
+            ("agriculture_turns", self.agriculture_turns),
+            ("masonry_turns", self.masonry_turns),
+            ("fortifications_turns", self.fortifications_turns),
+            ("logistics_turns", self.logistics_turns),
+            ("siege_turns", self.siege_turns),
+            ("plains_movement", self.plains_movement),
+            ("forest_movement", self.forest_movement),
+            ("mountain_movement", self.mountain_movement),
+            ("river_movement", self.river_movement),
+            ("deposit_movement", self.deposit_movement),
+        ];
+        for (key, value) in nonzero {
+            if value == 0 {
+                errors.push(error(format!("`{}` cannot be 0", key)));
+            }
+        }
+
+        let levels = [
+            (
+                "easy_starting_player_level",
+                self.easy_starting_player_level,
+            ),
+            ("easy_starting_enemy_level", self.easy_starting_enemy_level),
+            (
+                "standard_starting_player_level",
+                self.standard_starting_player_level,
+            ),
+            (
+                "standard_starting_enemy_level",
+                self.standard_starting_enemy_level,
+            ),
+            (
+                "hard_starting_player_level",
+                self.hard_starting_player_level,
+            ),
+            ("hard_starting_enemy_level", self.hard_starting_enemy_level),
+            (
+                "unfair_starting_player_level",
+                self.unfair_starting_player_level,
+            ),
+            (
+                "unfair_starting_enemy_level",
+                self.unfair_starting_enemy_level,
+            ),
+            ("neutral_combat_level", self.neutral_combat_level),
+            ("barbarian_combat_level", self.barbarian_combat_level),
+        ];
+        for (key, value) in levels {
+            if value == 0 {
+                errors.push(error(format!("`{}` must be at least 1", key)));
+            }
+        }
+
+        // Past this, generating a map where everyone can reach each other
+        // could take forever.
+        let wall_chances = [
+            ("easy_wall_chance_percent", self.easy_wall_chance_percent),
+            (
+                "standard_wall_chance_percent",
+                self.standard_wall_chance_percent,
+            ),
+            ("hard_wall_chance_percent", self.hard_wall_chance_percent),
+            (
+                "unfair_wall_chance_percent",
+                self.unfair_wall_chance_percent,
+            ),
+        ];
+        for (key, value) in wall_chances {
+            if value > 50 {
+                errors.push(error(format!("`{}` cannot be more than 50", key)));
+            }
+        }
+
+        let percentages = [
+            ("easy_event_chance_percent", self.easy_event_chance_percent),
+            (
+                "standard_event_chance_percent",
+                self.standard_event_chance_percent,
+            ),
+            ("hard_event_chance_percent", self.hard_event_chance_percent),
+            (
+                "unfair_event_chance_percent",
+                self.unfair_event_chance_percent,
+            ),
+            ("ai_capture_odds_percent", self.ai_capture_odds_percent),
+            ("ai_raze_odds_percent", self.ai_raze_odds_percent),
+            // A discount, so Masonry never makes clearing dearer.
+            ("masonry_cost_percent", self.masonry_cost_percent),
+        ];
+        for (key, value) in percentages {
+            if value > 100 {
+                errors.push(error(format!("`{}` cannot be more than 100", key)));
+            }
+        }
+
+        let event_weights = [
+            (
+                "easy",
+                self.easy_event_chance_percent,
+                self.event_weights(GameDifficulty::Easy),
+            ),
+            (
+                "standard",
+                self.standard_event_chance_percent,
+                self.event_weights(GameDifficulty::Standard),
+            ),
+            (
+                "hard",
+                self.hard_event_chance_percent,
+                self.event_weights(GameDifficulty::Hard),
+            ),
+            (
+                "unfair",
+                self.unfair_event_chance_percent,
+                self.event_weights(GameDifficulty::NotEvenRemotelyFair),
+            ),
+        ];
+        for (difficulty, chance, weights) in event_weights {
+            if chance > 0 && weights.iter().all(|&weight| weight == 0) {
+                errors.push(error(format!(
+                    "`{0}_plague_weight`, `{0}_harvest_weight`, `{0}_earthquake_weight` and `{0}_rebellion_weight` cannot all be 0",
+                    difficulty
+                )));
+            }
+        }
+
+        // `place_city` keeps looking for a free cell, so there has to be one for
+        // every city. Both sides' starting cities and the Hill take three.
+        let free_cells = (GRID_SIZE * GRID_SIZE - 3) as u32;
+        let cities = [
+            ("easy", self.easy_neutral_cities, self.easy_barbarian_camps),
+            (
+                "standard",
+                self.standard_neutral_cities,
+                self.standard_barbarian_camps,
+            ),
+            ("hard", self.hard_neutral_cities, self.hard_barbarian_camps),
+            (
+                "unfair",
+                self.unfair_neutral_cities,
+                self.unfair_barbarian_camps,
+            ),
+        ];
+        for (difficulty, neutral, barbarian) in cities {
+            if neutral.saturating_add(barbarian) > free_cells {
+                errors.push(error(format!(
+                    "`{0}_neutral_cities` plus `{0}_barbarian_camps` cannot be more than {1}",
+                    difficulty, free_cells
+                )));
+            }
+        }
+
+        if self.domination_percent > 100 {
+            errors.push(error("`domination_percent` cannot be more than 100".into()));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn defaults() -> Rules {
+        let mut rules = Rules::default();
+        rules
+            .apply(DEFAULT_RULES, "built-in rules")
+            .expect("the built-in rules should always parse");
+        rules
+    }
+
+    // The built-in rules, with `rules.cfg` on top if there is one, and then
+    // `overrides` (e.g. from a scenario) given as `(text, source)`.
+    pub fn load(overrides: Option<(&str, &str)>) -> Result<Rules, Vec<ConfigError>> {
+        let mut rules = Rules::defaults();
+
+        // Checked on its own first, so its mistakes aren't blamed on the overrides.
+        if let Some(text) = read_optional_file(RULES_FILE)? {
+            rules.apply(&text, RULES_FILE)?;
+            rules.validate(RULES_FILE)?;
+        }
+
+        if let Some((text, source)) = overrides {
+            rules.apply(text, source)?;
+            rules.validate(source)?;
+        }
+        Ok(rules)
+    }
+}
+
+// Must be called before anything asks for `rules()` to take effect.
+pub fn set_rules(rules: Rules) {
+    let _ = RULES.set(rules);
+}
+
+pub fn rules() -> &'static Rules {
+    RULES.get_or_init(Rules::defaults)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_values_skips_comments_and_reports_lines_without_equals() {
+        let text = "# a comment\n\n  attack_range = 6 # trailing\nname=two words\nnonsense\n";
+        let mut errors = Vec::new();
+        let pairs = key_values(text, "test", true, &mut errors);
+        assert_eq!(pairs, [(3, "attack_range", "6"), (4, "name", "two words")]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "test:5: expected `key = value`, found `nonsense`"
+        );
+
+        // Free text keeps its `#`, but whole-line comments are still skipped.
+        let mut errors = Vec::new();
+        let pairs = key_values("# a comment\nname = Turn #3\n", "test", false, &mut errors);
+        assert_eq!(pairs, [(2, "name", "Turn #3")]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn apply_only_takes_known_rules_and_whole_numbers() {
+        let mut rules = Rules::defaults();
+        let errors = rules
+            .apply(
+                "attack_range = 6\nno_such_rule = 1\nbuild_range = far\n",
+                "test",
+            )
+            .unwrap_err();
+        let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "unknown rule `no_such_rule`",
+                "`build_range` must be a whole number, found `far`"
+            ]
+        );
+        assert_eq!(rules.attack_range, 6);
+        assert_eq!(rules.build_range, Rules::defaults().build_range);
+    }
+
+    #[test]
+    fn validate_rejects_values_that_break_the_game() {
+        let mut rules = Rules::defaults();
+        rules.attack_range = 0;
+        rules.hard_neutral_cities = 200;
+        rules.easy_plague_weight = 0;
+        rules.easy_harvest_weight = 0;
+        rules.easy_earthquake_weight = 0;
+        rules.easy_rebellion_weight = 0;
+        let errors = rules.validate("test").unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].message.contains("attack_range"));
+        assert!(errors[1].message.contains("easy_plague_weight"));
+        assert!(errors[2].message.contains("hard_neutral_cities"));
+
+        // With events never happening, the weights don't matter.
+        rules.attack_range = 4;
+        rules.hard_neutral_cities = 2;
+        rules.easy_event_chance_percent = 0;
+        assert!(rules.validate("test").is_ok());
+
+        rules.economic_target = 0;
+        rules.masonry_cost_percent = 150;
+        let errors = rules.validate("test").unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].message.contains("economic_target"));
+        assert!(errors[1].message.contains("masonry_cost_percent"));
+    }
+}
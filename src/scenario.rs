@@ -0,0 +1,494 @@
+use std::{fs, io, path::Path};
+
+use crate::enums::*;
+use crate::rules::{ConfigError, Rules, key_values, rules};
+use crate::shared::*;
+use crate::structs::*;
+
+pub const CAMPAIGN_DIR: &str = "campaign";
+pub const CAMPAIGN_FILE: &str = "campaign.txt";
+pub const PROGRESS_FILE: &str = "campaign_progress.txt";
+
+// The campaign that ships with the game. A `campaign/campaign.txt` next to
+// the game replaces it.
+const BUILTIN_CAMPAIGN: [(&str, &str); 7] = [
+    ("campaign.txt", include_str!("campaign/campaign.txt")),
+    (
+        "landfall.scenario",
+        include_str!("campaign/landfall.scenario"),
+    ),
+    ("landfall.map", include_str!("campaign/landfall.map")),
+    (
+        "the_pass.scenario",
+        include_str!("campaign/the_pass.scenario"),
+    ),
+    ("the_pass.map", include_str!("campaign/the_pass.map")),
+    (
+        "long_winter.scenario",
+        include_str!("campaign/long_winter.scenario"),
+    ),
+    ("long_winter.map", include_str!("campaign/long_winter.map")),
+];
+
+fn read_campaign_file(name: &str) -> Result<String, ConfigError> {
+    let custom = Path::new(CAMPAIGN_DIR);
+    if custom.join(CAMPAIGN_FILE).exists() {
+        let path = custom.join(name);
+        return fs::read_to_string(&path).map_err(|e| ConfigError {
+            source: path.display().to_string(),
+            line: None,
+            message: format!("could not be read ({})", e.kind()),
+        });
+    }
+
+    BUILTIN_CAMPAIGN
+        .iter()
+        .find(|(file, _)| *file == name)
+        .map(|(_, text)| text.to_string())
+        .ok_or_else(|| ConfigError {
+            source: name.into(),
+            line: None,
+            message: "is not part of the built-in campaign".into(),
+        })
+}
+
+fn parse_difficulty(value: &str) -> Option<GameDifficulty> {
+    match value {
+        "easy" => Some(GameDifficulty::Easy),
+        "standard" => Some(GameDifficulty::Standard),
+        "hard" => Some(GameDifficulty::Hard),
+        "unfair" => Some(GameDifficulty::NotEvenRemotelyFair),
+        _ => None,
+    }
+}
+
+fn parse_victory(value: &str) -> Option<VictoryCondition> {
+    match value {
+        "domination" => Some(VictoryCondition::Domination),
+        "economic" => Some(VictoryCondition::Economic),
+        "wonder" => Some(VictoryCondition::Wonder),
+        "hill" => Some(VictoryCondition::KingOfTheHill),
+        _ => None,
+    }
+}
+
+fn parse_owner(value: &str) -> Option<CityState> {
+    match value {
+        "player" => Some(CityState::OwnedByPlayer),
+        "computer" => Some(CityState::OwnedByComputer),
+        "neutral" => Some(CityState::Neutral),
+        "barbarian" => Some(CityState::Barbarian),
+        _ => None,
+    }
+}
+
+// `reinforcements = TURN OWNER X Y LEVEL`, with the position on the map.
+fn parse_reinforcements(value: &str) -> Option<Trigger> {
+    let [turn, owner, x, y, level] = value.split_whitespace().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let position = TerminalPos(x.parse().ok()?, y.parse().ok()?);
+    if position.0 as usize >= GRID_SIZE || position.1 as usize >= GRID_SIZE {
+        return None;
+    }
+    Some(Trigger {
+        turn: turn.parse().ok()?,
+        effect: TriggerEffect::Reinforcements {
+            owner: parse_owner(owner)?,
+            position,
+            level: level.parse().ok().filter(|&level| level > 0)?,
+        },
+    })
+}
+
+// Map files use the in-game glyphs, plus plain ASCII stand-ins for the fancy ones.
+fn parse_map_cell(glyph: char) -> Option<Cell> {
+    let terrain = match glyph {
+        '.' => Terrain::Plains,
+        'f' | '♣' => Terrain::Forest,
+        '^' | '▲' => Terrain::Mountain,
+        '~' => Terrain::River,
+        '$' => Terrain::ResourceDeposit,
+        '#' => Terrain::Wall,
+        _ => {
+            let state = [
+                CityState::OwnedByPlayer,
+                CityState::OwnedByComputer,
+                CityState::Neutral,
+                CityState::Barbarian,
+                CityState::Destroyed,
+            ]
+            .into_iter()
+            .find(|state| state.glyph() == glyph)?;
+            return Some(Cell {
+                city: Some(City {
+                    state,
+                    ..City::default()
+                }),
+                terrain: Terrain::Plains,
+            });
+        }
+    };
+    Some(Cell {
+        city: None,
+        terrain,
+    })
+}
+
+pub fn parse_map(
+    text: &str,
+    source: &str,
+) -> Result<[[Cell; GRID_SIZE]; GRID_SIZE], Vec<ConfigError>> {
+    let error = |line: Option<usize>, message: String| ConfigError {
+        source: source.into(),
+        line,
+        message,
+    };
+    let mut map = [[Cell::default(); GRID_SIZE]; GRID_SIZE];
+    let mut errors = Vec::new();
+
+    let rows: Vec<&str> = text.lines().map(str::trim_end).collect();
+    if rows.len() != GRID_SIZE {
+        errors.push(error(
+            None,
+            format!("needs {} rows, found {}", GRID_SIZE, rows.len()),
+        ));
+    }
+
+    for (y, row) in rows.iter().take(GRID_SIZE).enumerate() {
+        if row.chars().count() != GRID_SIZE {
+            errors.push(error(
+                Some(y + 1),
+                format!("needs {} cells, found {}", GRID_SIZE, row.chars().count()),
+            ));
+            continue;
+        }
+        for (x, glyph) in row.chars().enumerate() {
+            match parse_map_cell(glyph) {
+                Some(cell) => map[y][x] = cell,
+                None => errors.push(error(Some(y + 1), format!("unknown map cell `{}`", glyph))),
+            }
+        }
+    }
+
+    let has = |state: CityState| {
+        map.iter()
+            .flatten()
+            .any(|cell| cell.city.is_some_and(|city| city.state == state))
+    };
+    if !has(CityState::OwnedByPlayer) || !has(CityState::OwnedByComputer) {
+        errors.push(error(
+            None,
+            "needs at least one player city and one computer city".into(),
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(map)
+    } else {
+        Err(errors)
+    }
+}
+
+impl Scenario {
+    pub fn parse(text: &str, source: &str) -> Result<Scenario, Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        let mut scenario = Scenario {
+            name: source.into(),
+            briefing: Vec::new(),
+            difficulty: GameDifficulty::Standard,
+            turn_limit: None,
+            events: false,
+            event_seed: None,
+            victory_conditions: Vec::new(),
+            rules: String::new(),
+            source: source.into(),
+            map: [[Cell::default(); GRID_SIZE]; GRID_SIZE],
+            triggers: Vec::new(),
+        };
+        let mut map_file = None;
+
+        for (line, key, value) in key_values(text, source, false, &mut errors) {
+            let mut error = |message: String| {
+                errors.push(ConfigError {
+                    source: source.into(),
+                    line: Some(line),
+                    message,
+                })
+            };
+
+            match key {
+                "name" => scenario.name = value.into(),
+                "briefing" => scenario.briefing.push(value.into()),
+                "map" => map_file = Some(value.to_string()),
+                "difficulty" => match parse_difficulty(value) {
+                    Some(difficulty) => scenario.difficulty = difficulty,
+                    None => error(format!("unknown difficulty `{}`", value)),
+                },
+                "turn_limit" => match value.parse() {
+                    Ok(turns) if turns > 0 => scenario.turn_limit = Some(turns),
+                    _ => error(format!("`turn_limit` must be above 0, found `{}`", value)),
+                },
+                "events" => match value {
+                    "on" => scenario.events = true,
+                    "off" => scenario.events = false,
+                    _ => error(format!("`events` must be `on` or `off`, found `{}`", value)),
+                },
+                "event_seed" => match value.parse() {
+                    Ok(seed) => scenario.event_seed = Some(seed),
+                    _ => error(format!(
+                        "`event_seed` must be a whole number, found `{}`",
+                        value
+                    )),
+                },
+                "victory" => match parse_victory(value) {
+                    Some(condition) => scenario.victory_conditions.push(condition),
+                    None => error(format!("unknown victory condition `{}`", value)),
+                },
+                "reinforcements" => match parse_reinforcements(value) {
+                    Some(trigger) => scenario.triggers.push(trigger),
+                    None => error("expected `reinforcements = TURN OWNER X Y LEVEL`".into()),
+                },
+                // `message = TURN TEXT`
+                "message" => {
+                    let parsed = value
+                        .split_once(' ')
+                        .and_then(|(turn, text)| Some((turn.parse().ok()?, text.trim())));
+                    match parsed {
+                        Some((turn, text)) => scenario.triggers.push(Trigger {
+                            turn,
+                            effect: TriggerEffect::Message(text.into()),
+                        }),
+                        None => error("expected `message = TURN TEXT`".into()),
+                    }
+                }
+                // `rule.KEY = VALUE` is checked along with the rest of the rules.
+                _ => match key.strip_prefix("rule.") {
+                    Some(rule) => {
+                        scenario.rules.push_str(&format!("{} = {}\n", rule, value));
+                    }
+                    None => error(format!("unknown scenario setting `{}`", key)),
+                },
+            }
+        }
+
+        match map_file {
+            Some(file) => match read_campaign_file(&file) {
+                Ok(text) => match parse_map(&text, &file) {
+                    Ok(map) => scenario.map = map,
+                    Err(map_errors) => errors.extend(map_errors),
+                },
+                Err(error) => errors.push(error),
+            },
+            None => errors.push(ConfigError {
+                source: source.into(),
+                line: None,
+                message: "needs a `map = FILE` line".into(),
+            }),
+        }
+
+        if errors.is_empty() {
+            Ok(scenario)
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Where the Hill would be, which has to be somewhere a city can stand.
+    pub fn hill_is_blocked(&self) -> bool {
+        let center = GRID_SIZE / 2;
+        self.victory_conditions
+            .contains(&VictoryCondition::KingOfTheHill)
+            && !self.map[center][center].terrain.is_buildable()
+    }
+}
+
+impl Campaign {
+    pub fn load() -> Result<Campaign, Vec<ConfigError>> {
+        let text = read_campaign_file(CAMPAIGN_FILE).map_err(|error| vec![error])?;
+        let mut errors = Vec::new();
+        let mut campaign = Campaign {
+            name: "Campaign".into(),
+            scenarios: Vec::new(),
+        };
+
+        for (line, key, value) in key_values(&text, CAMPAIGN_FILE, false, &mut errors) {
+            match key {
+                "name" => campaign.name = value.into(),
+                "scenario" => {
+                    let scenario = read_campaign_file(value)
+                        .map_err(|error| vec![error])
+                        .and_then(|text| Scenario::parse(&text, value));
+                    // Check the scenario's rules now rather than when it's picked.
+                    let scenario = scenario.and_then(|scenario| {
+                        let mut rules = Rules::defaults();
+                        rules.apply(&scenario.rules, value)?;
+                        rules.validate(value)?;
+                        Ok(scenario)
+                    });
+                    match scenario {
+                        Ok(scenario) if scenario.hill_is_blocked() => errors.push(ConfigError {
+                            source: value.into(),
+                            line: None,
+                            message: "the Hill in the middle of the map has to be buildable".into(),
+                        }),
+                        Ok(scenario) => campaign.scenarios.push(scenario),
+                        Err(scenario_errors) => errors.extend(scenario_errors),
+                    }
+                }
+                _ => errors.push(ConfigError {
+                    source: CAMPAIGN_FILE.into(),
+                    line: Some(line),
+                    message: format!("unknown campaign setting `{}`", key),
+                }),
+            }
+        }
+
+        if campaign.scenarios.is_empty() && errors.is_empty() {
+            errors.push(ConfigError {
+                source: CAMPAIGN_FILE.into(),
+                line: None,
+                message: "needs at least one `scenario = FILE` line".into(),
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(campaign)
+        } else {
+            Err(errors)
+        }
+    }
+
+    // How many scenarios of this campaign have been beaten, from the progress file.
+    pub fn completed(&self) -> usize {
+        let Ok(text) = fs::read_to_string(PROGRESS_FILE) else {
+            return 0;
+        };
+        let mut errors = Vec::new();
+        let pairs = key_values(&text, PROGRESS_FILE, false, &mut errors);
+
+        let same_campaign = pairs
+            .iter()
+            .any(|&(_, key, value)| key == "campaign" && value == self.name);
+        if !same_campaign {
+            return 0;
+        }
+        pairs
+            .iter()
+            .find(|&&(_, key, _)| key == "completed")
+            .and_then(|&(_, _, value)| value.parse().ok())
+            .map_or(0, |completed: usize| completed.min(self.scenarios.len()))
+    }
+
+    pub fn save_completed(&self, completed: usize) -> io::Result<()> {
+        if completed <= self.completed() {
+            return Ok(());
+        }
+        fs::write(
+            PROGRESS_FILE,
+            format!("campaign = {}\ncompleted = {}\n", self.name, completed),
+        )
+    }
+}
+
+impl Game {
+    pub fn start_scenario(&mut self, scenario: &Scenario) {
+        self.difficulty = scenario.difficulty;
+        self.turn_limit = scenario.turn_limit;
+        self.victory_conditions = scenario.victory_conditions.clone();
+        self.triggers = scenario.triggers.clone();
+        self.grid = scenario.map;
+
+        for (y, row) in self.grid.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                let Some(city) = cell.city.as_mut() else {
+                    continue;
+                };
+                let (level, combat, resources) = match city.state {
+                    CityState::OwnedByPlayer => {
+                        self.cursor_loc = TerminalPos(x as u16, y as u16);
+                        let level = self.difficulty.starting_player_level();
+                        (level, level, self.difficulty.starting_resources())
+                    }
+                    CityState::OwnedByComputer => {
+                        let level = self.difficulty.starting_enemy_level();
+                        (level, level, self.difficulty.starting_resources())
+                    }
+                    CityState::Neutral => (1, rules().neutral_combat_level, 0),
+                    CityState::Barbarian => {
+                        self.barbarians = true;
+                        (1, rules().barbarian_combat_level, 0)
+                    }
+                    CityState::Destroyed => (1, 1, 0),
+                };
+                city.generation_level = level;
+                city.combat_level = combat;
+                city.resources = resources;
+            }
+        }
+
+        self.log_event(format!("Scenario: {}.", scenario.name));
+        if scenario.events {
+            self.events = true;
+            self.event_seed = scenario.event_seed.unwrap_or_else(rand::random);
+            self.log_event(format!("World events are on (seed {}).", self.event_seed));
+        }
+    }
+
+    // Fires every trigger whose turn has come.
+    pub fn run_triggers(&mut self) {
+        let (due, pending): (Vec<Trigger>, Vec<Trigger>) = std::mem::take(&mut self.triggers)
+            .into_iter()
+            .partition(|trigger| trigger.turn <= self.turn);
+        self.triggers = pending;
+
+        for trigger in due {
+            match trigger.effect {
+                TriggerEffect::Message(text) => {
+                    self.status = Some("New orders have arrived. Check the log.");
+                    self.log_event(text);
+                }
+                TriggerEffect::Reinforcements {
+                    owner,
+                    position,
+                    level,
+                } => self.reinforce(owner, position, level),
+            }
+        }
+    }
+
+    // Strengthens a city `owner` already holds at `position`, or raises a new
+    // one there if the cell is free.
+    fn reinforce(&mut self, owner: CityState, position: TerminalPos, level: u32) {
+        let Ok(cell) = self.get_mut_cell_at_pos(position) else {
+            return;
+        };
+        match cell.city.as_mut() {
+            Some(city) if city.state == owner => city.combat_level += level,
+            Some(_) => {
+                self.log_event(format!(
+                    "{} reinforcements could not reach {}.",
+                    owner, position
+                ));
+                return;
+            }
+            None => {
+                if !cell.terrain.is_buildable() {
+                    cell.terrain = Terrain::Plains;
+                }
+                cell.city = Some(City {
+                    state: owner,
+                    generation_level: level,
+                    combat_level: level,
+                    ..City::default()
+                });
+            }
+        }
+
+        self.status = Some(match owner {
+            CityState::OwnedByPlayer => "Reinforcements have arrived!",
+            _ => "Enemy reinforcements have arrived!",
+        });
+        self.log_event(format!("{} reinforcements arrived at {}.", owner, position));
+    }
+}
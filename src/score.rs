@@ -0,0 +1,108 @@
+use crate::enums::*;
+use crate::rules::rules;
+use crate::shared::*;
+use crate::structs::*;
+use crate::theme::theme;
+
+impl Score {
+    pub fn total(&self) -> u32 {
+        let rules = rules();
+        self.cities * rules.score_per_city
+            + self.levels * rules.score_per_level
+            + self.resources / rules.score_resources_divisor
+            + self.destroyed * rules.score_per_city_destroyed
+    }
+}
+
+impl Game {
+    pub fn score(&self, owner: CityState) -> Score {
+        let mut score = Score {
+            destroyed: match owner {
+                CityState::OwnedByPlayer => self.player_cities_destroyed,
+                CityState::OwnedByComputer => self.computer_cities_destroyed,
+                _ => 0,
+            },
+            ..Score::default()
+        };
+
+        for pos in self.city_positions(Some(owner)) {
+            let Ok(city) = self.get_city_at_pos(pos) else {
+                continue;
+            };
+            score.cities += 1;
+            score.levels += city.generation_level + city.combat_level;
+            score.resources += city.resources;
+        }
+        score
+    }
+
+    pub fn count_destroyed(&mut self, attacker: CityState) {
+        match attacker {
+            CityState::OwnedByPlayer => self.player_cities_destroyed += 1,
+            CityState::OwnedByComputer => self.computer_cities_destroyed += 1,
+            _ => {}
+        }
+    }
+
+    pub fn turn_limit_reached(&self) -> bool {
+        self.turn_limit.is_some_and(|limit| self.turn >= limit)
+    }
+
+    // Once the last turn is played, the higher score wins. Only an exact tie is a stalemate.
+    pub fn check_turn_limit(&mut self) {
+        if self.is_over() || !self.turn_limit_reached() {
+            return;
+        }
+
+        let player = self.score(CityState::OwnedByPlayer).total();
+        let computer = self.score(CityState::OwnedByComputer).total();
+        self.state = match player.cmp(&computer) {
+            std::cmp::Ordering::Greater => GameState::PlayerWon,
+            std::cmp::Ordering::Less => GameState::ComputerWon,
+            std::cmp::Ordering::Equal => GameState::Stalemate,
+        };
+        if self.state != GameState::Stalemate {
+            self.victory = Some(VictoryCondition::Score);
+        }
+        self.log_event(format!(
+            "Time is up! Final score: Player {}, Computer {}.",
+            player, computer
+        ));
+    }
+
+    // Draws from `screen_y` down, and returns the first row left free below.
+    pub fn draw_score(&self, frame: &mut Frame, screen_y: u16) -> u16 {
+        let screen_x = PANEL_X as u16;
+
+        let turn = match self.turn_limit {
+            Some(limit) => format!("Turn {} of {}", self.turn, limit),
+            None => format!("Turn {}", self.turn),
+        };
+        let theme = theme();
+        frame.put(screen_x, screen_y, &turn, theme.background, theme.text);
+
+        // The enemy's points would give away levels and resources you can't
+        // see, so they only show once the game is over.
+        let mut rows = vec![("You", CityState::OwnedByPlayer)];
+        if self.is_over() {
+            rows.push(("Enemy", CityState::OwnedByComputer));
+        }
+        for (i, (label, owner)) in rows.into_iter().enumerate() {
+            let score = self.score(owner);
+            frame.put(
+                screen_x,
+                screen_y + 2 + i as u16,
+                &format!(
+                    "{:<6}{:>4} pts ({} cities, {} razed)",
+                    label,
+                    score.total(),
+                    score.cities,
+                    score.destroyed
+                ),
+                theme.background,
+                owner.color(),
+            );
+        }
+        screen_y + 5
+    }
+}
@@ -0,0 +1,160 @@
+use std::{env, fs, io, sync::Mutex};
+
+use crate::enums::{ColorSupport, ThemeName};
+use crate::rules::{ConfigError, key_values, read_optional_file};
+use crate::structs::Settings;
+
+pub const SETTINGS_FILE: &str = "settings.cfg";
+
+static SETTINGS: Mutex<Settings> = Mutex::new(Settings::DEFAULT);
+
+// Reads an environment variable, treating an empty one as unset.
+fn env(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings::DEFAULT
+    }
+}
+
+impl Settings {
+    const DEFAULT: Settings = Settings {
+        theme: ThemeName::Default,
+        colors: ColorSupport::TrueColor,
+        unicode: true,
+        animations: true,
+    };
+
+    // Follows NO_COLOR (https://no-color.org), TERM/COLORTERM and the locale.
+    pub fn detect_terminal(&mut self) {
+        let term = env("TERM").unwrap_or_default();
+        let colorterm = env("COLORTERM").unwrap_or_default();
+        self.colors = if env("NO_COLOR").is_some() || term == "dumb" {
+            ColorSupport::None
+        } else if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Extended
+        } else if cfg!(windows) && term.is_empty() {
+            // Windows terminals don't set TERM, and modern ones all do 24-bit color.
+            ColorSupport::TrueColor
+        } else {
+            ColorSupport::Basic
+        };
+
+        // The first of these that is set decides, as it does for the C library.
+        let locale = env("LC_ALL")
+            .or_else(|| env("LC_CTYPE"))
+            .or_else(|| env("LANG"))
+            .unwrap_or_default()
+            .to_lowercase();
+        self.unicode = if locale.is_empty() {
+            cfg!(windows)
+        } else {
+            locale.contains("utf-8") || locale.contains("utf8")
+        };
+    }
+
+    // Applies every `key = value` line of `text` on top of the current settings.
+    pub fn apply(&mut self, text: &str, source: &str) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        for (line, key, value) in key_values(text, source, true, &mut errors) {
+            let error = |message: String| ConfigError {
+                source: source.into(),
+                line: Some(line),
+                message,
+            };
+
+            match key {
+                "theme" => match ThemeName::ALL
+                    .into_iter()
+                    .find(|theme| theme.config_name() == value)
+                {
+                    Some(theme) => self.theme = theme,
+                    None => errors.push(error(format!("unknown theme `{}`", value))),
+                },
+                "animations" => match value {
+                    "on" => self.animations = true,
+                    "off" => self.animations = false,
+                    _ => errors.push(error(format!("expected `on` or `off`, found `{}`", value))),
+                },
+                _ => errors.push(error(format!("unknown setting `{}`", key))),
+            }
+        }
+
+        errors.sort_by_key(|error| error.line);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // The defaults, with `settings.cfg` on top if there is one.
+    pub fn load() -> Result<Settings, Vec<ConfigError>> {
+        let mut settings = Settings::default();
+        settings.detect_terminal();
+
+        if let Some(text) = read_optional_file(SETTINGS_FILE)? {
+            settings.apply(&text, SETTINGS_FILE)?;
+        }
+
+        Ok(settings)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        fs::write(
+            SETTINGS_FILE,
+            format!(
+                "# Cell Wars settings. Change them from the Settings menu, or here.\ntheme = {}\nanimations = {}\n",
+                self.theme.config_name(),
+                if self.animations { "on" } else { "off" }
+            ),
+        )
+    }
+}
+
+pub fn set_settings(settings: Settings) {
+    *SETTINGS.lock().unwrap_or_else(|e| e.into_inner()) = settings;
+}
+
+pub fn settings() -> Settings {
+    *SETTINGS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_overrides_saved_settings() {
+        let mut settings = Settings::default();
+        let text = "# saved\ntheme = deuteranopia\nanimations = off # too slow\n";
+        assert_eq!(settings.apply(text, "test"), Ok(()));
+        assert_eq!(settings.theme, ThemeName::Deuteranopia);
+        assert!(!settings.animations);
+        // What the terminal can do never comes from the file.
+        assert_eq!(settings.colors, Settings::DEFAULT.colors);
+    }
+
+    #[test]
+    fn apply_rejects_unknown_themes_and_settings() {
+        let mut settings = Settings::default();
+        let errors = settings
+            .apply("theme = neon\nanimations = yes\nvolume = 11\n", "test")
+            .unwrap_err();
+        let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "unknown theme `neon`",
+                "expected `on` or `off`, found `yes`",
+                "unknown setting `volume`"
+            ]
+        );
+        assert_eq!(settings, Settings::default());
+    }
+}
@@ -1,8 +1,10 @@
 use crossterm::{
     cursor,
-    event::{KeyCode, KeyEvent},
+    event::{DisableMouseCapture, KeyEvent},
     execute,
-    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    style::{
+        Attribute, Color, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+    },
     terminal::{self, Clear, ClearType, LeaveAlternateScreen},
 };
 use rand::prelude::*;
@@ -11,13 +13,29 @@ use std::{
     process::exit,
 };
 
-use crate::{enums::RawCommand, structs::*};
+use crate::{
+    enums::{ColorSupport, RawCommand, Terrain},
+    keymap::keymap,
+    render::{invalidate_frame, is_highlighted},
+    settings::settings,
+    structs::*,
+    theme::terminal_color,
+};
 
 pub const GRID_SIZE: usize = 10;
 
 pub const GRID_X_OFFSET: usize = 5;
 pub const GRID_Y_OFFSET: usize = 1;
 
+// The score, objectives and tutorial sit to the right of the grid.
+pub const PANEL_X: usize = GRID_X_OFFSET + GRID_SIZE * 3 + 2;
+pub const PANEL_WIDTH: usize = 40;
+
+// How many of the latest log entries are shown under the grid.
+pub const LOG_LINES_SHOWN: usize = 3;
+
+pub const TURN_LIMIT_CHOICES: [u32; 3] = [30, 50, 100];
+
 pub fn random_coord<R: Rng>(rng: &mut R) -> TerminalPos {
     TerminalPos(
         rng.random_range(0..GRID_SIZE) as u16,
@@ -25,36 +43,48 @@ pub fn random_coord<R: Rng>(rng: &mut R) -> TerminalPos {
     )
 }
 
-pub fn move_cursor(x: u16, y: u16) -> io::Result<()> {
-    let mut stdout = stdout();
-    execute!(stdout, cursor::MoveTo(x, y))?;
-    Ok(())
+// Walls are rolled separately, since how many there are depends on difficulty.
+pub fn random_terrain<R: Rng>(rng: &mut R) -> Terrain {
+    match rng.random_range(0..100) {
+        0..55 => Terrain::Plains,
+        55..75 => Terrain::Forest,
+        75..85 => Terrain::Mountain,
+        85..95 => Terrain::River,
+        _ => Terrain::ResourceDeposit,
+    }
 }
 
 pub fn key_to_command(key: KeyEvent) -> RawCommand {
-    match key.code {
-        KeyCode::Up => RawCommand::MoveUp,
-        KeyCode::Down => RawCommand::MoveDown,
-        KeyCode::Left => RawCommand::MoveLeft,
-        KeyCode::Right => RawCommand::MoveRight,
-        KeyCode::Enter => RawCommand::Interact,
-        KeyCode::Char('s') => RawCommand::EndTurn,
-        KeyCode::Esc => RawCommand::QuitGame,
-        _ => RawCommand::None,
-    }
+    keymap().command(key.code)
 }
 
 pub fn clear_screen() -> io::Result<()> {
+    // Whatever was on screen is gone, so the next frame has to be drawn in full.
+    invalidate_frame();
     let mut stdout = stdout();
     execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
     Ok(())
 }
 
 pub fn print_with_color(text: &str, background: Color, foreground: Color) -> io::Result<()> {
+    if settings().colors == ColorSupport::None {
+        let attribute = if is_highlighted(background) {
+            Attribute::Reverse
+        } else {
+            Attribute::NoReverse
+        };
+        execute!(
+            stdout(),
+            SetAttribute(attribute),
+            Print(text),
+            SetAttribute(Attribute::Reset)
+        )?;
+        return Ok(());
+    }
     execute!(
         stdout(),
-        SetForegroundColor(foreground),
-        SetBackgroundColor(background),
+        SetForegroundColor(terminal_color(foreground)),
+        SetBackgroundColor(terminal_color(background)),
         Print(text),
         ResetColor
     )?;
@@ -77,6 +107,11 @@ pub fn wait_for_enter(silent: bool) -> io::Result<()> {
 
 pub fn quit(code: i32) -> ! {
     let _ = terminal::disable_raw_mode();
-    let _ = execute!(io::stdout(), cursor::Show, LeaveAlternateScreen);
+    let _ = execute!(
+        io::stdout(),
+        DisableMouseCapture,
+        cursor::Show,
+        LeaveAlternateScreen
+    );
     exit(code);
 }
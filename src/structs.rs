@@ -1,3 +1,5 @@
+use crossterm::{event::KeyCode, style::Color};
+
 use crate::enums::*;
 use crate::shared::*;
 
@@ -6,19 +8,206 @@ pub struct GameGuard;
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
 pub struct TerminalPos(pub u16, pub u16);
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScreenCell {
+    pub symbol: char,
+    pub background: Color,
+    pub foreground: Color,
+}
+
+// The whole screen as it should look, built up before anything is printed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub width: u16,
+    pub height: u16,
+    pub cells: Vec<ScreenCell>,
+}
+
+// A roll that was made, and the range it could have landed anywhere in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Roll {
+    pub value: u32,
+    pub min: u32,
+    pub max: u32,
+}
+
+// The exact odds of an attack, worked out before anything is rolled. Rolls
+// include every bonus, and the chances add up to 1.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CombatPreview {
+    pub attack: (u32, u32),
+    pub defense: (u32, u32),
+    pub success: f64,
+    pub held_out: f64,
+    pub repelled: f64,
+    pub expected_loss: f64,
+}
+
+// One thing a city could do right now, and why it can't if it can't.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ActionOption {
+    pub action: GameActionSkeleton,
+    pub cost: u32,
+    pub target: TargetKind,
+    pub blocked: Option<GameError>,
+}
+
+// One line of a popup menu. Entries with a reason are shown but can't be chosen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MenuItem {
+    pub label: String,
+    pub detail: String,
+    pub help: String,
+    pub reason: Option<&'static str>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Menu {
+    pub title: String,
+    pub items: Vec<MenuItem>,
+    pub selected: usize,
+}
+
+// Every color the game draws with, by what it's used for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub background: Color,
+    pub text: Color,
+    pub muted: Color,
+    pub faint: Color,
+    pub accent: Color,
+    pub warning: Color,
+    pub good: Color,
+    pub selection: Color,
+    pub selection_text: Color,
+    pub hill: Color,
+    pub player: Color,
+    pub computer: Color,
+    pub neutral: Color,
+    pub barbarian: Color,
+    pub ruins: Color,
+    pub plains: Color,
+    pub forest: Color,
+    pub mountain: Color,
+    pub river: Color,
+    pub deposit: Color,
+    pub wall: Color,
+    pub attack_range: Color,
+    pub build_range: Color,
+    pub threat: Color,
+    pub reachable: Color,
+}
+
+// The theme is saved. What the terminal can do is worked out at startup.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub theme: ThemeName,
+    pub colors: ColorSupport,
+    pub unicode: bool,
+    pub animations: bool,
+}
+
+// Which keys trigger each command, the first one being the one shown on screen.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Keymap {
+    pub bindings: Vec<(RawCommand, Vec<KeyCode>)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
 pub struct Game {
     pub grid: [[Cell; GRID_SIZE]; GRID_SIZE],
     pub difficulty: GameDifficulty,
     pub cursor_loc: TerminalPos,
     pub state: GameState,
     pub status: Option<&'static str>,
+    pub turn: u32,
+    pub barbarians: bool,
+    pub events: bool,
+    pub event_seed: u64,
+    pub turn_limit: Option<u32>,
+    pub player_cities_destroyed: u32,
+    pub computer_cities_destroyed: u32,
+    pub victory_conditions: Vec<VictoryCondition>,
+    pub victory: Option<VictoryCondition>,
+    pub player_objectives: Objectives,
+    pub computer_objectives: Objectives,
+    pub log: Vec<String>,
+    pub convoys: Vec<Convoy>,
+    pub player_research: Research,
+    pub computer_research: Research,
+    pub triggers: Vec<Trigger>,
+    pub tutorial: Option<TutorialStep>,
+    pub last_action: Option<GameAction>,
+    pub overlay: Overlay,
+    pub animations: Vec<Animation>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trigger {
+    pub turn: u32,
+    pub effect: TriggerEffect,
+}
+
+// One map of a campaign, with everything needed to start it. Cities on the
+// map only have an owner until the game starts and gives them their levels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scenario {
+    pub name: String,
+    pub briefing: Vec<String>,
+    pub difficulty: GameDifficulty,
+    pub turn_limit: Option<u32>,
+    pub events: bool,
+    pub event_seed: Option<u64>,
+    pub victory_conditions: Vec<VictoryCondition>,
+    pub rules: String,
+    pub source: String,
+    pub map: [[Cell; GRID_SIZE]; GRID_SIZE],
+    pub triggers: Vec<Trigger>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Campaign {
+    pub name: String,
+    pub scenarios: Vec<Scenario>,
+}
+
+// How long a faction has held the objectives that take more than one turn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct Objectives {
+    pub wonder_turns: u32,
+    pub hill_turns: u32,
+}
+
+// What decides the winner once the turn limit runs out.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct Score {
+    pub cities: u32,
+    pub levels: u32,
+    pub resources: u32,
+    pub destroyed: u32,
+}
+
+// Technology is shared by every city of a faction.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Research {
+    pub known: Vec<Tech>,
+    pub current: Option<Tech>,
+    pub turns_left: u32,
+}
+
+// Resources on their way from one city to another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Convoy {
+    pub owner: CityState,
+    pub amount: u32,
+    pub position: TerminalPos,
+    pub route: Vec<TerminalPos>, // Cells still left to travel, in order.
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
 pub struct Cell {
     pub city: Option<City>,
-    pub blocked: bool, // If blocked, there can be no City.
+    pub terrain: Terrain, // If it's a wall, there can be no City.
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -27,4 +216,6 @@ pub struct City {
     pub generation_level: u32,
     pub combat_level: u32,
     pub resources: u32,
+    pub has_acted: bool, // Each city may take one action per turn.
+    pub buildings: [bool; Building::ALL.len()], // Indexed by `Building as usize`.
 }
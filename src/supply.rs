@@ -0,0 +1,156 @@
+use crate::enums::*;
+use crate::pathfinding::neighbours;
+use crate::structs::*;
+
+impl Game {
+    // Both ends of a transfer have to be living cities of the same owner.
+    fn require_supply_line(
+        &self,
+        source: TerminalPos,
+        target: TerminalPos,
+    ) -> Result<CityState, GameError> {
+        let owner = self.get_source_city_at_pos(source)?.state;
+        let receiver = self.get_city_at_pos(target)?;
+        if receiver.state == CityState::Destroyed {
+            return Err(GameError::NoCityAtTarget);
+        }
+        if receiver.state != owner {
+            return Err(GameError::NotYourCity);
+        }
+        Ok(owner)
+    }
+
+    // Sends the source's whole stockpile at once, minus a fee for the distance.
+    pub fn transfer_resources(
+        &mut self,
+        source: TerminalPos,
+        target: TerminalPos,
+    ) -> Result<(), GameError> {
+        let owner = self.require_supply_line(source, target)?;
+        let distance = self.require_path_within(source, target, u32::MAX)?;
+        let fee = distance * self.transfer_fee_per_step(owner);
+
+        let amount = self.get_source_city_at_pos(source)?.resources;
+        if amount <= fee {
+            return Err(GameError::NotEnoughResources);
+        }
+
+        self.get_mut_source_city_at_pos(source)?.resources = 0;
+        self.get_mut_city_at_pos(target)?.resources += amount - fee;
+        self.log_event(format!(
+            "{} moved {} Resources from {} to {} ({} lost on the way).",
+            owner,
+            amount - fee,
+            source,
+            target,
+            fee
+        ));
+        Ok(())
+    }
+
+    // Sends the source's whole stockpile along the road, with no fee.
+    pub fn send_convoy(
+        &mut self,
+        source: TerminalPos,
+        target: TerminalPos,
+    ) -> Result<(), GameError> {
+        let owner = self.require_supply_line(source, target)?;
+        let route = self
+            .find_path(source, target)
+            .ok_or(GameError::TargetNotReachable)?;
+
+        let amount = self.get_source_city_at_pos(source)?.resources;
+        if amount == 0 {
+            return Err(GameError::NotEnoughResources);
+        }
+
+        self.get_mut_source_city_at_pos(source)?.resources = 0;
+        self.convoys.push(Convoy {
+            owner,
+            amount,
+            position: source,
+            route,
+        });
+        self.log_event(format!(
+            "{} sent a convoy of {} Resources from {} to {}.",
+            owner, amount, source, target
+        ));
+        Ok(())
+    }
+
+    pub fn convoy_at(&self, pos: TerminalPos) -> Option<&Convoy> {
+        self.convoys.iter().find(|convoy| convoy.position == pos)
+    }
+
+    // Any city that would actually steal from the convoy, standing right
+    // next to where it is. Neutral cities keep to themselves.
+    fn interceptor_near(&self, pos: TerminalPos, owner: CityState) -> Option<TerminalPos> {
+        neighbours(pos).find(|&next| {
+            self.get_city_at_pos(next).is_ok_and(|city| {
+                !matches!(city.state, CityState::Neutral | CityState::Destroyed)
+                    && city.state != owner
+            })
+        })
+    }
+
+    // Moves every convoy of `owner` along its route, delivering or losing it.
+    pub fn advance_convoys(&mut self, owner: CityState) {
+        let mut convoys = std::mem::take(&mut self.convoys);
+
+        convoys.retain_mut(|convoy| {
+            if convoy.owner != owner {
+                return true;
+            }
+
+            let steps = self.convoy_speed(owner).min(convoy.route.len());
+            for pos in convoy.route.drain(..steps).collect::<Vec<_>>() {
+                convoy.position = pos;
+
+                if let Some(thief) = self.interceptor_near(pos, owner) {
+                    let Ok(city) = self.get_mut_city_at_pos(thief) else {
+                        continue;
+                    };
+                    city.resources += convoy.amount;
+                    let thief_state = city.state;
+                    self.status = Some(match (owner, thief_state) {
+                        (CityState::OwnedByPlayer, _) => "One of your convoys was intercepted!",
+                        (_, CityState::OwnedByPlayer) => "You intercepted an enemy convoy!",
+                        _ => "A convoy was intercepted.",
+                    });
+                    self.log_event(format!(
+                        "{} intercepted a {} convoy of {} Resources at {}.",
+                        thief_state, owner, convoy.amount, pos
+                    ));
+                    return false;
+                }
+            }
+
+            if !convoy.route.is_empty() {
+                return true;
+            }
+
+            // Whoever holds the destination when the convoy arrives gets it.
+            let destination = convoy.position;
+            match self.get_mut_source_city_at_pos(destination) {
+                Ok(city) => {
+                    city.resources += convoy.amount;
+                    let receiver = city.state;
+                    self.log_event(format!(
+                        "A {} convoy delivered {} Resources to the {} city at {}.",
+                        owner, convoy.amount, receiver, destination
+                    ));
+                }
+                Err(_) => {
+                    self.log_event(format!(
+                        "A {} convoy found nothing left at {}.",
+                        owner, destination
+                    ));
+                }
+            }
+            false
+        });
+
+        convoys.append(&mut self.convoys);
+        self.convoys = convoys;
+    }
+}
@@ -0,0 +1,349 @@
+use std::{fmt, io};
+
+use crate::enums::*;
+use crate::keymap::keymap;
+use crate::render::draw_too_small;
+use crate::rules::rules;
+use crate::structs::*;
+use crate::theme::theme;
+
+impl fmt::Display for Tech {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Tech::Agriculture => "Agriculture",
+            Tech::Masonry => "Masonry",
+            Tech::Fortifications => "Fortifications",
+            Tech::Logistics => "Logistics",
+            Tech::Siege => "Siege",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl Tech {
+    pub const ALL: [Tech; 5] = [
+        Tech::Agriculture,
+        Tech::Masonry,
+        Tech::Fortifications,
+        Tech::Logistics,
+        Tech::Siege,
+    ];
+
+    pub fn cost(self) -> u32 {
+        let rules = rules();
+        match self {
+            Tech::Agriculture => rules.agriculture_cost,
+            Tech::Masonry => rules.masonry_cost,
+            Tech::Fortifications => rules.fortifications_cost,
+            Tech::Logistics => rules.logistics_cost,
+            Tech::Siege => rules.siege_cost,
+        }
+    }
+
+    pub fn research_turns(self) -> u32 {
+        let rules = rules();
+        match self {
+            Tech::Agriculture => rules.agriculture_turns,
+            Tech::Masonry => rules.masonry_turns,
+            Tech::Fortifications => rules.fortifications_turns,
+            Tech::Logistics => rules.logistics_turns,
+            Tech::Siege => rules.siege_turns,
+        }
+    }
+
+    pub fn prerequisite(self) -> Option<Tech> {
+        match self {
+            Tech::Agriculture | Tech::Masonry => None,
+            Tech::Fortifications => Some(Tech::Masonry),
+            Tech::Logistics => Some(Tech::Agriculture),
+            Tech::Siege => Some(Tech::Fortifications),
+        }
+    }
+
+    pub fn effect(self) -> String {
+        let rules = rules();
+        match self {
+            Tech::Agriculture => format!(
+                "+{} Resources every time a city produces.",
+                rules.agriculture_production
+            ),
+            Tech::Masonry => format!(
+                "Walls and ruins cost {}% as much to clear.",
+                rules.masonry_cost_percent
+            ),
+            Tech::Fortifications => {
+                format!("+{} to every defense roll.", rules.fortifications_defense)
+            }
+            Tech::Logistics => format!(
+                "Free transfers, +{} convoy speed, +{} build range.",
+                rules.logistics_convoy_speed, rules.logistics_build_range
+            ),
+            Tech::Siege => format!(
+                "+{} to every attack roll, and captures need no extra margin.",
+                rules.siege_attack
+            ),
+        }
+    }
+}
+
+impl Research {
+    pub fn knows(&self, tech: Tech) -> bool {
+        self.known.contains(&tech)
+    }
+}
+
+impl Game {
+    pub fn research(&self, owner: CityState) -> Option<&Research> {
+        match owner {
+            CityState::OwnedByPlayer => Some(&self.player_research),
+            CityState::OwnedByComputer => Some(&self.computer_research),
+            _ => None,
+        }
+    }
+
+    fn research_mut(&mut self, owner: CityState) -> Option<&mut Research> {
+        match owner {
+            CityState::OwnedByPlayer => Some(&mut self.player_research),
+            CityState::OwnedByComputer => Some(&mut self.computer_research),
+            _ => None,
+        }
+    }
+
+    pub fn has_tech(&self, owner: CityState, tech: Tech) -> bool {
+        self.research(owner)
+            .is_some_and(|research| research.knows(tech))
+    }
+
+    // Resources across every city of `owner`, which is what research draws from.
+    pub fn total_resources(&self, owner: CityState) -> u32 {
+        self.city_positions(Some(owner))
+            .iter()
+            .filter_map(|&pos| self.get_city_at_pos(pos).ok())
+            .map(|city| city.resources)
+            .sum()
+    }
+
+    pub fn can_research(&self, owner: CityState, tech: Tech) -> Result<(), GameError> {
+        let research = self.research(owner).ok_or(GameError::NotYourCity)?;
+        if research.knows(tech) {
+            return Err(GameError::TechAlreadyKnown);
+        }
+        if research.current.is_some() {
+            return Err(GameError::AlreadyResearching);
+        }
+        if tech
+            .prerequisite()
+            .is_some_and(|needed| !research.knows(needed))
+        {
+            return Err(GameError::TechLocked);
+        }
+        if self.total_resources(owner) < tech.cost() {
+            return Err(GameError::NotEnoughResources);
+        }
+        Ok(())
+    }
+
+    // Pays for `tech` out of the richest cities first and starts researching it.
+    pub fn start_research(&mut self, owner: CityState, tech: Tech) -> Result<(), GameError> {
+        self.can_research(owner, tech)?;
+
+        let mut cities = self.city_positions(Some(owner));
+        cities.sort_by_key(|&pos| {
+            std::cmp::Reverse(self.get_city_at_pos(pos).map_or(0, |city| city.resources))
+        });
+
+        let mut owed = tech.cost();
+        for pos in cities {
+            let city = self.get_mut_city_at_pos(pos)?;
+            let paid = owed.min(city.resources);
+            city.resources -= paid;
+            owed -= paid;
+            if owed == 0 {
+                break;
+            }
+        }
+
+        if let Some(research) = self.research_mut(owner) {
+            research.current = Some(tech);
+            research.turns_left = tech.research_turns();
+        }
+        self.log_event(format!("{} started researching {}.", owner, tech));
+        Ok(())
+    }
+
+    pub fn advance_research(&mut self, owner: CityState) {
+        let Some(research) = self.research_mut(owner) else {
+            return;
+        };
+        let Some(tech) = research.current else {
+            return;
+        };
+
+        research.turns_left = research.turns_left.saturating_sub(1);
+        if research.turns_left > 0 {
+            return;
+        }
+
+        research.known.push(tech);
+        research.current = None;
+        if owner == CityState::OwnedByPlayer {
+            self.status = Some("Research complete!");
+        }
+        self.log_event(format!("{} finished researching {}.", owner, tech));
+    }
+
+    // Rule values that technology changes.
+
+    pub fn attack_bonus(&self, owner: CityState) -> u32 {
+        if self.has_tech(owner, Tech::Siege) {
+            rules().siege_attack
+        } else {
+            0
+        }
+    }
+
+    pub fn defense_bonus(&self, owner: CityState) -> u32 {
+        if self.has_tech(owner, Tech::Fortifications) {
+            rules().fortifications_defense
+        } else {
+            0
+        }
+    }
+
+    pub fn production_bonus(&self, owner: CityState) -> u32 {
+        if self.has_tech(owner, Tech::Agriculture) {
+            rules().agriculture_production
+        } else {
+            0
+        }
+    }
+
+    pub fn capture_margin(&self, owner: CityState) -> u32 {
+        if self.has_tech(owner, Tech::Siege) {
+            0
+        } else {
+            rules().capture_margin
+        }
+    }
+
+    pub fn demolish_cost(&self, owner: CityState, ruins: bool) -> u32 {
+        let cost = if ruins {
+            rules().ruins_clear_cost
+        } else {
+            rules().wall_destroy_cost
+        };
+        if self.has_tech(owner, Tech::Masonry) {
+            cost.saturating_mul(rules().masonry_cost_percent)
+                .div_ceil(100)
+        } else {
+            cost
+        }
+    }
+
+    pub fn transfer_fee_per_step(&self, owner: CityState) -> u32 {
+        if self.has_tech(owner, Tech::Logistics) {
+            0
+        } else {
+            rules().transfer_fee_per_step
+        }
+    }
+
+    pub fn convoy_speed(&self, owner: CityState) -> usize {
+        if self.has_tech(owner, Tech::Logistics) {
+            (rules().convoy_speed + rules().logistics_convoy_speed) as usize
+        } else {
+            rules().convoy_speed as usize
+        }
+    }
+
+    pub fn build_range(&self, owner: CityState) -> u32 {
+        if self.has_tech(owner, Tech::Logistics) {
+            rules().build_range + rules().logistics_build_range
+        } else {
+            rules().build_range
+        }
+    }
+
+    // The tech screen, drawn over the whole terminal like the game itself.
+    pub fn tech_tree_frame(&self, selected: usize) -> io::Result<Frame> {
+        let mut frame = Frame::for_terminal()?;
+        if Layout::for_size(frame.width, frame.height).is_none() {
+            draw_too_small(&mut frame);
+            return Ok(frame);
+        }
+        let theme = theme();
+        frame.put(0, 0, "Technology", theme.background, theme.accent);
+
+        let research = &self.player_research;
+        for (i, tech) in Tech::ALL.iter().enumerate() {
+            let (state, color) = if research.knows(*tech) {
+                ("Known".to_string(), theme.good)
+            } else if research.current == Some(*tech) {
+                (
+                    format!("Researching, {} turn(s) left", research.turns_left),
+                    theme.warning,
+                )
+            } else if let Some(needed) = tech
+                .prerequisite()
+                .filter(|needed| !research.knows(*needed))
+            {
+                (format!("Needs {}", needed), theme.faint)
+            } else {
+                ("Available".to_string(), theme.text)
+            };
+
+            let marker = if i == selected { '>' } else { ' ' };
+            frame.put(
+                0,
+                (2 + i * 2) as u16,
+                &format!(
+                    "{} {} - {} Resources, {} turns [{}]",
+                    marker,
+                    tech,
+                    tech.cost(),
+                    tech.research_turns(),
+                    state
+                ),
+                theme.background,
+                color,
+            );
+            frame.put(
+                4,
+                (3 + i * 2) as u16,
+                &tech.effect(),
+                theme.background,
+                theme.muted,
+            );
+        }
+
+        let footer_y = (3 + Tech::ALL.len() * 2) as u16;
+        frame.put(
+            0,
+            footer_y,
+            &format!(
+                "Your cities have {} Resources in total.",
+                self.total_resources(CityState::OwnedByPlayer)
+            ),
+            theme.background,
+            theme.muted,
+        );
+        let keys = keymap();
+        frame.put(
+            0,
+            footer_y + 1,
+            &format!(
+                "[{} to choose, {} to research, {} to go back]",
+                keys.key_names(&[RawCommand::MoveUp, RawCommand::MoveDown]),
+                keys.key_name(RawCommand::Interact),
+                keys.key_name(RawCommand::QuitGame)
+            ),
+            theme.background,
+            theme.accent,
+        );
+
+        if let Some(status) = self.status {
+            frame.put(0, footer_y + 3, status, theme.background, theme.warning);
+        }
+        Ok(frame)
+    }
+}
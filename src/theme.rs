@@ -0,0 +1,256 @@
+use crossterm::style::Color;
+use std::fmt;
+
+use crate::enums::{ColorSupport, ThemeName};
+use crate::settings::settings;
+use crate::structs::Theme;
+
+// Okabe-Ito colors, picked to stay apart under the common kinds of colorblindness.
+const SKY_BLUE: Color = Color::Rgb {
+    r: 86,
+    g: 180,
+    b: 233,
+};
+const ORANGE: Color = Color::Rgb {
+    r: 230,
+    g: 159,
+    b: 0,
+};
+const BLUISH_GREEN: Color = Color::Rgb {
+    r: 0,
+    g: 158,
+    b: 115,
+};
+const YELLOW: Color = Color::Rgb {
+    r: 240,
+    g: 228,
+    b: 66,
+};
+const BLUE: Color = Color::Rgb {
+    r: 0,
+    g: 114,
+    b: 178,
+};
+const REDDISH_PURPLE: Color = Color::Rgb {
+    r: 204,
+    g: 121,
+    b: 167,
+};
+
+const DEFAULT: Theme = Theme {
+    background: Color::Black,
+    text: Color::White,
+    muted: Color::Grey,
+    faint: Color::DarkGrey,
+    accent: Color::Cyan,
+    warning: Color::Yellow,
+    good: Color::Green,
+    selection: Color::Blue,
+    selection_text: Color::White,
+    hill: Color::DarkYellow,
+    player: Color::Cyan,
+    computer: Color::Red,
+    neutral: Color::White,
+    barbarian: Color::Magenta,
+    ruins: Color::DarkRed,
+    // Every terrain gets its own color, and none of them is a city's.
+    plains: Color::Grey,
+    forest: Color::DarkGreen,
+    mountain: Color::DarkMagenta,
+    river: Color::Blue,
+    deposit: Color::Yellow,
+    wall: Color::DarkGrey,
+    attack_range: Color::Rgb {
+        r: 95,
+        g: 25,
+        b: 25,
+    },
+    build_range: Color::Rgb {
+        r: 25,
+        g: 75,
+        b: 25,
+    },
+    threat: Color::Rgb {
+        r: 130,
+        g: 60,
+        b: 0,
+    },
+    reachable: Color::Rgb {
+        r: 25,
+        g: 40,
+        b: 95,
+    },
+};
+
+const HIGH_CONTRAST: Theme = Theme {
+    muted: Color::White,
+    faint: Color::Grey,
+    ruins: Color::DarkGrey,
+    plains: Color::DarkGrey,
+    forest: Color::Green,
+    wall: Color::White,
+    ..DEFAULT
+};
+
+const DEUTERANOPIA: Theme = Theme {
+    accent: SKY_BLUE,
+    warning: YELLOW,
+    good: BLUISH_GREEN,
+    selection: BLUE,
+    player: SKY_BLUE,
+    computer: ORANGE,
+    barbarian: REDDISH_PURPLE,
+    ruins: Color::DarkGrey,
+    forest: BLUISH_GREEN,
+    river: BLUE,
+    deposit: YELLOW,
+    // Darker Okabe-Ito shades, so the letters on top stay readable.
+    attack_range: Color::Rgb {
+        r: 115,
+        g: 80,
+        b: 0,
+    },
+    build_range: Color::Rgb { r: 0, g: 80, b: 58 },
+    threat: Color::Rgb {
+        r: 102,
+        g: 60,
+        b: 84,
+    },
+    reachable: Color::Rgb { r: 0, g: 57, b: 89 },
+    ..DEFAULT
+};
+
+// Reds look dark to protanopes, so the enemy is yellow rather than orange.
+const PROTANOPIA: Theme = Theme {
+    computer: YELLOW,
+    deposit: ORANGE,
+    ..DEUTERANOPIA
+};
+
+const MONOCHROME: Theme = Theme {
+    background: Color::Black,
+    text: Color::White,
+    muted: Color::White,
+    faint: Color::Grey,
+    accent: Color::White,
+    warning: Color::White,
+    good: Color::White,
+    selection: Color::White,
+    selection_text: Color::Black,
+    hill: Color::DarkGrey,
+    player: Color::White,
+    computer: Color::White,
+    neutral: Color::White,
+    barbarian: Color::White,
+    ruins: Color::Grey,
+    plains: Color::Grey,
+    forest: Color::White,
+    mountain: Color::White,
+    river: Color::White,
+    deposit: Color::White,
+    wall: Color::White,
+    attack_range: Color::DarkGrey,
+    build_range: Color::DarkGrey,
+    threat: Color::DarkGrey,
+    reachable: Color::DarkGrey,
+};
+
+impl ThemeName {
+    pub const ALL: [ThemeName; 5] = [
+        ThemeName::Default,
+        ThemeName::HighContrast,
+        ThemeName::Deuteranopia,
+        ThemeName::Protanopia,
+        ThemeName::Monochrome,
+    ];
+
+    pub fn config_name(self) -> &'static str {
+        match self {
+            ThemeName::Default => "default",
+            ThemeName::HighContrast => "high_contrast",
+            ThemeName::Deuteranopia => "deuteranopia",
+            ThemeName::Protanopia => "protanopia",
+            ThemeName::Monochrome => "monochrome",
+        }
+    }
+
+    pub fn theme(self) -> Theme {
+        match self {
+            ThemeName::Default => DEFAULT,
+            ThemeName::HighContrast => HIGH_CONTRAST,
+            ThemeName::Deuteranopia => DEUTERANOPIA,
+            ThemeName::Protanopia => PROTANOPIA,
+            ThemeName::Monochrome => MONOCHROME,
+        }
+    }
+}
+
+impl fmt::Display for ThemeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            ThemeName::Default => "Default",
+            ThemeName::HighContrast => "High Contrast",
+            ThemeName::Deuteranopia => "Deuteranopia (red-green)",
+            ThemeName::Protanopia => "Protanopia (red-weak)",
+            ThemeName::Monochrome => "Monochrome",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+// The colors of the theme picked in the settings, or plain monochrome if the
+// terminal can't show colors at all.
+pub fn theme() -> Theme {
+    let settings = settings();
+    if settings.colors == ColorSupport::None {
+        MONOCHROME
+    } else {
+        settings.theme.theme()
+    }
+}
+
+// The 16 standard colors, roughly as most terminals show them.
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// Swaps 24-bit colors for the closest ones the terminal can actually show.
+pub fn terminal_color(color: Color) -> Color {
+    let Color::Rgb { r, g, b } = color else {
+        return color;
+    };
+    match settings().colors {
+        ColorSupport::TrueColor | ColorSupport::None => color,
+        // The 6x6x6 color cube of the 256-color palette.
+        ColorSupport::Extended => {
+            let level = |c: u8| (c as u16 * 5 / 255) as u8;
+            Color::AnsiValue(16 + 36 * level(r) + 6 * level(g) + level(b))
+        }
+        ColorSupport::Basic => {
+            let distance = |(br, bg, bb): (u8, u8, u8)| {
+                (r as i32 - br as i32).pow(2)
+                    + (g as i32 - bg as i32).pow(2)
+                    + (b as i32 - bb as i32).pow(2)
+            };
+            BASIC_COLORS
+                .into_iter()
+                .min_by_key(|&(_, rgb)| distance(rgb))
+                .map_or(color, |(basic, _)| basic)
+        }
+    }
+}
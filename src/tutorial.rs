@@ -0,0 +1,224 @@
+use crate::enums::*;
+use crate::keymap::keymap;
+use crate::rules::rules;
+use crate::scenario::parse_map;
+use crate::shared::*;
+use crate::structs::*;
+use crate::theme::theme;
+
+const TUTORIAL_MAP: &str = include_str!("tutorial.map");
+// The one wall standing between the player and the enemy.
+const TUTORIAL_WALL: TerminalPos = TerminalPos(3, 1);
+
+impl TutorialStep {
+    pub fn next(self) -> Option<TutorialStep> {
+        match self {
+            TutorialStep::MoveCursor => Some(TutorialStep::Produce),
+            TutorialStep::Produce => Some(TutorialStep::EndTurn),
+            TutorialStep::EndTurn => Some(TutorialStep::Upgrade),
+            TutorialStep::Upgrade => Some(TutorialStep::FoundCity),
+            TutorialStep::FoundCity => Some(TutorialStep::DestroyWall),
+            TutorialStep::DestroyWall => Some(TutorialStep::Attack),
+            TutorialStep::Attack => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TutorialStep::MoveCursor => "Moving the cursor",
+            TutorialStep::Produce => "Producing",
+            TutorialStep::EndTurn => "Ending the turn",
+            TutorialStep::Upgrade => "Upgrading",
+            TutorialStep::FoundCity => "Founding a city",
+            TutorialStep::DestroyWall => "Destroying a wall",
+            TutorialStep::Attack => "Attacking",
+        }
+    }
+
+    pub fn instructions(self) -> String {
+        let keys = keymap();
+        let text = match self {
+            TutorialStep::MoveCursor => {
+                return format!(
+                    "Welcome! Use {} or the mouse to move the cursor onto your city, the P.",
+                    keys.key_names(&[
+                        RawCommand::MoveUp,
+                        RawCommand::MoveDown,
+                        RawCommand::MoveLeft,
+                        RawCommand::MoveRight
+                    ])
+                );
+            }
+            TutorialStep::Produce => {
+                return format!(
+                    "Cities need Resources for everything. Press {} on your city and choose Produce Resources.",
+                    keys.key_name(RawCommand::Interact)
+                );
+            }
+            TutorialStep::EndTurn => {
+                return format!(
+                    "Each city can only act once per turn. Press {} to end your turn.",
+                    keys.key_name(RawCommand::EndTurn)
+                );
+            }
+            TutorialStep::Upgrade => {
+                "Upgrades make a city stronger. Choose one of the Upgrade actions. Combat Readiness wins fights, Production earns more."
+            }
+            TutorialStep::FoundCity => {
+                "More cities means more actions. Choose Build New City and pick an empty tile close to your city."
+            }
+            TutorialStep::DestroyWall => {
+                "The wall (#) cuts you off from the enemy. Choose Destroy Wall and target the wall right next to your city."
+            }
+            TutorialStep::Attack => {
+                "Now attack the enemy's C with Attack City. If you're repelled, end your turn and try again."
+            }
+        };
+        text.into()
+    }
+}
+
+// Splits `text` into lines no longer than `width`, breaking between words.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.into()),
+        }
+    }
+    lines
+}
+
+impl Game {
+    // A fixed map where the enemy never moves, walking through one action at a time.
+    pub fn start_tutorial(&mut self) {
+        self.grid =
+            parse_map(TUTORIAL_MAP, "tutorial.map").expect("the tutorial map should always parse");
+        for cell in self.grid.iter_mut().flatten() {
+            if let Some(city) = cell.city.as_mut() {
+                city.generation_level = 1;
+                city.combat_level = 1;
+            }
+        }
+        self.cursor_loc = TerminalPos(7, 6);
+        self.tutorial = Some(TutorialStep::MoveCursor);
+        self.log_event("Tutorial started.".into());
+    }
+
+    // Moves on to the next step once the current one has been done.
+    pub fn advance_tutorial(&mut self) {
+        let Some(step) = self.tutorial else {
+            return;
+        };
+        let done = match step {
+            TutorialStep::MoveCursor => self
+                .get_city_at_pos(self.cursor_loc)
+                .is_ok_and(|city| city.state == CityState::OwnedByPlayer),
+            TutorialStep::Produce => matches!(self.last_action, Some(GameAction::Produce { .. })),
+            TutorialStep::EndTurn => false,
+            TutorialStep::Upgrade => matches!(
+                self.last_action,
+                Some(GameAction::UpgradeAttack { .. } | GameAction::UpgradeProduce { .. })
+            ),
+            TutorialStep::FoundCity => {
+                matches!(self.last_action, Some(GameAction::GenerateCity { .. }))
+            }
+            TutorialStep::DestroyWall => {
+                matches!(
+                    self.last_action,
+                    Some(GameAction::DestroyWall { target, .. }) if target == TUTORIAL_WALL
+                )
+            }
+            TutorialStep::Attack => self.state == GameState::PlayerWon,
+        };
+
+        // A repelled attack still counts as trying.
+        let retrying = step == TutorialStep::Attack
+            && matches!(self.last_action, Some(GameAction::AttackCity { .. }));
+
+        if done {
+            self.finish_tutorial_step(step);
+        } else if self.last_action.is_some() && !retrying {
+            self.status = Some("That's not this step. Read the tutorial on the right.");
+            self.last_action = None;
+        }
+    }
+
+    pub fn tutorial_turn_ended(&mut self) {
+        if self.tutorial == Some(TutorialStep::EndTurn) {
+            self.finish_tutorial_step(TutorialStep::EndTurn);
+        }
+    }
+
+    fn finish_tutorial_step(&mut self, step: TutorialStep) {
+        self.tutorial = step.next();
+        self.last_action = None;
+        self.status = Some("Well done!");
+        self.log_event(format!("Tutorial: {} done.", step.name()));
+
+        // Make sure the next step can always be done right away.
+        let needed = match self.tutorial {
+            Some(TutorialStep::Upgrade) => self
+                .city_positions(Some(CityState::OwnedByPlayer))
+                .iter()
+                .filter_map(|&pos| self.get_city_at_pos(pos).ok())
+                .map(|city| {
+                    city.get_resource_amount_to_upgrade_attack()
+                        .max(city.get_resource_amount_to_upgrade_produce())
+                })
+                .max()
+                .unwrap_or(0),
+            Some(TutorialStep::FoundCity) => rules().city_found_cost,
+            Some(TutorialStep::DestroyWall) => self.demolish_cost(CityState::OwnedByPlayer, false),
+            _ => 0,
+        };
+        let trained = self.tutorial == Some(TutorialStep::Attack);
+
+        for pos in self.city_positions(Some(CityState::OwnedByPlayer)) {
+            let Ok(city) = self.get_mut_city_at_pos(pos) else {
+                continue;
+            };
+            city.has_acted = false;
+            city.resources = city.resources.max(needed);
+            if trained {
+                city.combat_level = city.combat_level.max(6);
+            }
+        }
+    }
+
+    pub fn draw_tutorial(&self, frame: &mut Frame, layout: Layout, screen_y: u16) -> u16 {
+        let Some(step) = self.tutorial else {
+            return screen_y;
+        };
+        let screen_x = PANEL_X as u16;
+
+        let theme = theme();
+        frame.put(
+            screen_x,
+            screen_y,
+            "Tutorial",
+            theme.background,
+            theme.accent,
+        );
+        // In the compact layout the instructions have to end above the grid's bottom edge.
+        let gap = match layout {
+            Layout::Regular => 2,
+            Layout::Compact => 1,
+        };
+        let lines = wrap(&step.instructions(), PANEL_WIDTH);
+        for (i, line) in lines.iter().enumerate() {
+            frame.put(
+                screen_x,
+                screen_y + gap + i as u16,
+                line,
+                theme.background,
+                theme.text,
+            );
+        }
+        screen_y + gap + lines.len() as u16 + 1
+    }
+}
@@ -0,0 +1,271 @@
+use std::fmt;
+
+use crate::enums::*;
+use crate::rules::rules;
+use crate::shared::*;
+use crate::structs::*;
+use crate::theme::theme;
+
+impl fmt::Display for VictoryCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            VictoryCondition::Conquest => "Conquest",
+            VictoryCondition::Score => "Score",
+            VictoryCondition::Domination => "Domination",
+            VictoryCondition::Economic => "Economic",
+            VictoryCondition::Wonder => "Wonder",
+            VictoryCondition::KingOfTheHill => "King of the Hill",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl VictoryCondition {
+    // Conquest is always on, and Score comes with the turn limit.
+    pub const OPTIONAL: [VictoryCondition; 4] = [
+        VictoryCondition::Domination,
+        VictoryCondition::Economic,
+        VictoryCondition::Wonder,
+        VictoryCondition::KingOfTheHill,
+    ];
+
+    pub fn goal(self) -> String {
+        let rules = rules();
+        match self {
+            VictoryCondition::Conquest => "Destroy or capture every enemy city.".into(),
+            VictoryCondition::Score => "Have the higher score when time runs out.".into(),
+            VictoryCondition::Domination => {
+                format!("Hold {}% of all cities.", rules.domination_percent)
+            }
+            VictoryCondition::Economic => {
+                format!("Stockpile {} Resources.", rules.economic_target)
+            }
+            VictoryCondition::Wonder => {
+                format!(
+                    "Build a Wonder and hold it for {} turns.",
+                    rules.wonder_hold_turns
+                )
+            }
+            VictoryCondition::KingOfTheHill => format!(
+                "Hold a city on the Hill for {} turns.",
+                rules.hill_hold_turns
+            ),
+        }
+    }
+}
+
+struct ConditionChoice(VictoryCondition);
+
+impl fmt::Display for ConditionChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.0, self.0.goal())
+    }
+}
+
+impl Game {
+    pub fn has_condition(&self, condition: VictoryCondition) -> bool {
+        self.victory_conditions.contains(&condition)
+    }
+
+    pub fn prompt_victory_conditions(&mut self) {
+        let choices = VictoryCondition::OPTIONAL.map(ConditionChoice).into();
+        let menu = inquire::MultiSelect::new("Choose extra ways to win.", choices)
+            .with_help_message("Conquest always wins. Space to toggle, enter to confirm.");
+        self.victory_conditions = menu
+            .prompt()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|choice| choice.0)
+            .collect();
+    }
+
+    // The marked center cell, if King of the Hill is being played.
+    pub fn hill(&self) -> Option<TerminalPos> {
+        self.has_condition(VictoryCondition::KingOfTheHill)
+            .then_some(TerminalPos((GRID_SIZE / 2) as u16, (GRID_SIZE / 2) as u16))
+    }
+
+    pub fn is_hill_reachable(&self) -> bool {
+        let Some(hill) = self.hill() else {
+            return true;
+        };
+        let Some(&first) = self.city_positions(None).first() else {
+            return true;
+        };
+        self.distance_map_for(first, None)[hill.1 as usize][hill.0 as usize].is_some()
+    }
+
+    fn objectives(&self, owner: CityState) -> Objectives {
+        match owner {
+            CityState::OwnedByPlayer => self.player_objectives,
+            CityState::OwnedByComputer => self.computer_objectives,
+            _ => Objectives::default(),
+        }
+    }
+
+    fn objectives_mut(&mut self, owner: CityState) -> Option<&mut Objectives> {
+        match owner {
+            CityState::OwnedByPlayer => Some(&mut self.player_objectives),
+            CityState::OwnedByComputer => Some(&mut self.computer_objectives),
+            _ => None,
+        }
+    }
+
+    fn domination_progress(&self, owner: CityState) -> (u32, u32) {
+        let total = self.city_positions(None).len() as u32;
+        let needed = (total * rules().domination_percent).div_ceil(100).max(1);
+        (self.city_positions(Some(owner)).len() as u32, needed)
+    }
+
+    fn holds_wonder(&self, owner: CityState) -> bool {
+        self.city_positions(Some(owner)).into_iter().any(|pos| {
+            self.get_city_at_pos(pos)
+                .is_ok_and(|city| city.has_building(Building::Wonder))
+        })
+    }
+
+    fn holds_hill(&self, owner: CityState) -> bool {
+        self.hill()
+            .and_then(|hill| self.get_city_at_pos(hill).ok())
+            .is_some_and(|city| city.state == owner)
+    }
+
+    // How far `owner` is towards `condition`, as (current, needed).
+    fn progress(&self, owner: CityState, condition: VictoryCondition) -> Option<(u32, u32)> {
+        let rules = rules();
+        let objectives = self.objectives(owner);
+        match condition {
+            VictoryCondition::Conquest | VictoryCondition::Score => None,
+            VictoryCondition::Domination => Some(self.domination_progress(owner)),
+            VictoryCondition::Economic => {
+                Some((self.total_resources(owner), rules.economic_target))
+            }
+            VictoryCondition::Wonder => Some((objectives.wonder_turns, rules.wonder_hold_turns)),
+            VictoryCondition::KingOfTheHill => Some((objectives.hill_turns, rules.hill_hold_turns)),
+        }
+    }
+
+    fn met_condition(&self, owner: CityState) -> Option<VictoryCondition> {
+        self.victory_conditions.iter().copied().find(|&condition| {
+            self.progress(owner, condition)
+                .is_some_and(|(current, needed)| current >= needed)
+        })
+    }
+
+    // Run once both factions have moved, so holding something means holding it
+    // through the other side's turn.
+    pub fn check_victory_conditions(&mut self) {
+        if self.is_over() {
+            return;
+        }
+
+        for owner in [CityState::OwnedByPlayer, CityState::OwnedByComputer] {
+            let holds_wonder = self.holds_wonder(owner);
+            let holds_hill = self.holds_hill(owner);
+            let Some(objectives) = self.objectives_mut(owner) else {
+                continue;
+            };
+            objectives.wonder_turns = if holds_wonder {
+                objectives.wonder_turns + 1
+            } else {
+                0
+            };
+            objectives.hill_turns = if holds_hill {
+                objectives.hill_turns + 1
+            } else {
+                0
+            };
+        }
+
+        let player = self.met_condition(CityState::OwnedByPlayer);
+        let computer = self.met_condition(CityState::OwnedByComputer);
+        let (state, condition) = match (player, computer) {
+            (None, None) => return,
+            (Some(condition), None) => (GameState::PlayerWon, condition),
+            (None, Some(condition)) => (GameState::ComputerWon, condition),
+            (Some(condition), Some(_)) => {
+                self.state = GameState::Stalemate;
+                self.log_event(format!("Both sides won by {} at once.", condition));
+                return;
+            }
+        };
+
+        self.state = state;
+        self.victory = Some(condition);
+        let winner = if state == GameState::PlayerWon {
+            CityState::OwnedByPlayer
+        } else {
+            CityState::OwnedByComputer
+        };
+        self.log_event(format!("{} won by {}.", winner, condition));
+    }
+
+    // What the end screen says about how the game was decided.
+    pub fn victory_text(&self) -> String {
+        let player_won = self.state == GameState::PlayerWon;
+        match (self.state, self.victory) {
+            (GameState::Stalemate, _) => "It's a tie! Nobody came out ahead.".into(),
+            (GameState::PlayerWon | GameState::ComputerWon, Some(VictoryCondition::Conquest)) => {
+                if player_won {
+                    "You won by Conquest! Every enemy city has fallen.".into()
+                } else {
+                    "You lost to Conquest... Every one of your cities has fallen.".into()
+                }
+            }
+            (GameState::PlayerWon | GameState::ComputerWon, Some(VictoryCondition::Score)) => {
+                let player = self.score(CityState::OwnedByPlayer).total();
+                let computer = self.score(CityState::OwnedByComputer).total();
+                if player_won {
+                    format!("You won on Score, {} to {}!", player, computer)
+                } else {
+                    format!("You lost on Score, {} to {}...", player, computer)
+                }
+            }
+            (GameState::PlayerWon, Some(condition)) => {
+                format!("You won by {}! ({})", condition, condition.goal())
+            }
+            (GameState::ComputerWon, Some(condition)) => {
+                format!(
+                    "You lost... The computer won by {}. ({})",
+                    condition,
+                    condition.goal()
+                )
+            }
+            _ => "The game ended without a winner.".into(),
+        }
+    }
+
+    pub fn draw_objectives(&self, frame: &mut Frame, top: u16) -> u16 {
+        let screen_x = PANEL_X as u16;
+        let mut screen_y = top;
+
+        for &condition in &self.victory_conditions {
+            let Some((yours, needed)) = self.progress(CityState::OwnedByPlayer, condition) else {
+                continue;
+            };
+            let (theirs, _) = self
+                .progress(CityState::OwnedByComputer, condition)
+                .unwrap_or_default();
+
+            frame.put(
+                screen_x,
+                screen_y,
+                &format!(
+                    "{:<17}{:>4}/{:<4} enemy {}",
+                    condition.to_string(),
+                    yours,
+                    needed,
+                    theirs
+                ),
+                theme().background,
+                theme().muted,
+            );
+            screen_y += 1;
+        }
+        if screen_y > top {
+            screen_y + 1
+        } else {
+            screen_y
+        }
+    }
+}